@@ -0,0 +1,57 @@
+// Palette extraction on 4K and 8K wallpapers generated in memory, run with
+// `cargo bench` and again with `--features parallel` to compare
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use image::{Rgba, RgbaImage};
+use walrus::haishoku::{Focus, Haishoku, Resolution};
+
+const SIZES: [(&str, u32, u32); 2] = [("4k", 3840, 2160), ("8k", 7680, 4320)];
+
+// Diagonal gradients with a brighter disc in the middle, so every color group
+// gets some pixels
+fn wallpaper(width: u32, height: u32) -> RgbaImage {
+    RgbaImage::from_fn(width, height, |x, y| {
+        let u = x as f32 / width as f32;
+        let v = y as f32 / height as f32;
+        let (dx, dy) = (u - 0.5, v - 0.5);
+        let disc = if dx * dx + dy * dy < 0.04 { 80.0 } else { 0.0 };
+        Rgba([
+            (u * 175.0 + disc) as u8,
+            (v * 175.0 + disc) as u8,
+            ((1.0 - u) * (1.0 - v) * 175.0 + disc) as u8,
+            255,
+        ])
+    })
+}
+
+fn extraction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("extraction");
+    group.sample_size(20);
+
+    for (name, width, height) in SIZES {
+        let image = wallpaper(width, height);
+
+        group.bench_with_input(BenchmarkId::new("uniform", name), &image, |b, image| {
+            b.iter(|| Haishoku::new().load_haishoku(image).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("saliency", name), &image, |b, image| {
+            b.iter(|| {
+                let mut haishoku = Haishoku::new();
+                haishoku.set_focus(Focus::Center);
+                haishoku.set_saliency(true);
+                haishoku.load_haishoku(image).unwrap()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("full", name), &image, |b, image| {
+            b.iter(|| {
+                let mut haishoku = Haishoku::new();
+                haishoku.set_resolution(Resolution::Full);
+                haishoku.load_haishoku(image).unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, extraction);
+criterion_main!(benches);
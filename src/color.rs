@@ -1,30 +1,59 @@
+//! RGB colors and the conversions walrus needs (hex, HSL, YIQ, WCAG contrast)
+
+/// An sRGB color
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Color {
+    /// Red channel
     pub r: u8,
+    /// Green channel
     pub g: u8,
+    /// Blue channel
     pub b: u8,
 }
 
 impl Color {
+    /// Color from its red, green and blue channels
     pub fn new(r: u8, g: u8, b: u8) -> Self {
         Color { r, g, b }
     }
 
-    pub fn to_hex(&self) -> String {
+    /// Parse "#rrggbb" or "rrggbb"
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim().trim_start_matches('#');
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+        let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+        let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+        Some(Color::new(r, g, b))
+    }
+
+    /// "#rrggbb"
+    pub fn to_hex(self) -> String {
         format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
     }
 
-    pub fn to_hex_stripped(&self) -> String {
+    /// "rrggbb", without the leading #
+    pub fn to_hex_stripped(self) -> String {
         format!("{:02x}{:02x}{:02x}", self.r, self.g, self.b)
     }
 
+    /// "#rrggbbaa", as used by rofi, mako and friends
+    pub fn to_hex_alpha(self, alpha: u8) -> String {
+        format!("{}{:02x}", self.to_hex(), alpha)
+    }
+
+    /// "rgb(r, g, b)"
     #[allow(dead_code)]
-    pub fn to_rgb(&self) -> String {
+    pub fn to_rgb(self) -> String {
         format!("rgb({}, {}, {})", self.r, self.g, self.b)
     }
 
+    /// "hsl(h, s%, l%)"
     #[allow(dead_code)]
-    pub fn to_hsl(&self) -> String {
+    pub fn to_hsl(self) -> String {
         let r = self.r as f32 / 255.0;
         let g = self.g as f32 / 255.0;
         let b = self.b as f32 / 255.0;
@@ -60,8 +89,8 @@ impl Color {
         format!("hsl({}, {}%, {}%)", h, s, l)
     }
 
-    // Convert RGB to YIQ for luminance-based sorting (like pywal)
-    pub fn to_yiq(&self) -> f32 {
+    /// Convert RGB to YIQ for luminance-based sorting (like pywal)
+    pub fn to_yiq(self) -> f32 {
         let r = self.r as f32 / 255.0;
         let g = self.g as f32 / 255.0;
         let b = self.b as f32 / 255.0;
@@ -69,6 +98,27 @@ impl Color {
         0.299 * r + 0.587 * g + 0.114 * b
     }
 
+    /// WCAG relative luminance, from linearized sRGB
+    pub fn luminance(self) -> f32 {
+        let linear = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// WCAG contrast ratio, from 1.0 (same color) to 21.0 (black on white)
+    pub fn contrast_ratio(self, other: Color) -> f32 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// Scale each channel towards black, amount 0.0-1.0
     pub fn darken(&self, amount: f32) -> Color {
         let r = (self.r as f32 * (1.0 - amount)) as u8;
         let g = (self.g as f32 * (1.0 - amount)) as u8;
@@ -76,6 +126,7 @@ impl Color {
         Color::new(r, g, b)
     }
 
+    /// Move each channel towards white, amount 0.0-1.0
     pub fn lighten(&self, amount: f32) -> Color {
         let r = (self.r as f32 + (255.0 - self.r as f32) * amount) as u8;
         let g = (self.g as f32 + (255.0 - self.g as f32) * amount) as u8;
@@ -83,6 +134,81 @@ impl Color {
         Color::new(r, g, b)
     }
 
+    /// Linear blend towards other, amount 0.0 = self, 1.0 = other
+    pub fn mix(&self, other: &Color, amount: f32) -> Color {
+        let blend = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+        Color::new(
+            blend(self.r, other.r),
+            blend(self.g, other.g),
+            blend(self.b, other.b),
+        )
+    }
+
+    /// Hue in degrees, saturation and lightness in 0.0-1.0
+    pub fn to_hsl_components(self) -> (f32, f32, f32) {
+        let r = self.r as f32 / 255.0;
+        let g = self.g as f32 / 255.0;
+        let b = self.b as f32 / 255.0;
+
+        let max = r.max(g.max(b));
+        let min = r.min(g.min(b));
+        let diff = max - min;
+
+        let l = (max + min) / 2.0;
+
+        if diff == 0.0 {
+            return (0.0, 0.0, l);
+        }
+
+        let s = if l > 0.5 {
+            diff / (2.0 - max - min)
+        } else {
+            diff / (max + min)
+        };
+
+        let h = if max == r {
+            (g - b) / diff + if g < b { 6.0 } else { 0.0 }
+        } else if max == g {
+            (b - r) / diff + 2.0
+        } else {
+            (r - g) / diff + 4.0
+        };
+
+        (h * 60.0, s, l)
+    }
+
+    /// Color from hue in degrees, saturation and lightness in 0.0-1.0
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Color {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = c * (1.0 - ((h % 2.0) - 1.0).abs());
+        let m = l - c / 2.0;
+
+        let (r_prime, g_prime, b_prime) = if h < 1.0 {
+            (c, x, 0.0)
+        } else if h < 2.0 {
+            (x, c, 0.0)
+        } else if h < 3.0 {
+            (0.0, c, x)
+        } else if h < 4.0 {
+            (0.0, x, c)
+        } else if h < 5.0 {
+            (x, 0.0, c)
+        } else {
+            (c, 0.0, x)
+        };
+
+        Color::new(
+            ((r_prime + m) * 255.0).round() as u8,
+            ((g_prime + m) * 255.0).round() as u8,
+            ((b_prime + m) * 255.0).round() as u8,
+        )
+    }
+
+    /// Same hue and lightness with the saturation set to amount, 0.0-1.0
     pub fn saturate(&self, amount: f32) -> Color {
         let r = self.r as f32 / 255.0;
         let g = self.g as f32 / 255.0;
@@ -140,14 +266,18 @@ impl Color {
     }
 }
 
+/// A color with the number of pixels it covers
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct WeightedColor {
+    /// The color
     pub color: Color,
+    /// Number of pixels
     pub count: u32,
 }
 
 impl WeightedColor {
+    /// Color with its pixel count
     #[allow(dead_code)]
     pub fn new(color: Color, count: u32) -> Self {
         WeightedColor { color, count }
@@ -163,6 +293,14 @@ mod tests {
         assert_eq!(color.to_hex(), "#ff8000");
     }
 
+    #[test]
+    fn test_color_from_hex() {
+        assert_eq!(Color::from_hex("#ff8000"), Some(Color::new(255, 128, 0)));
+        assert_eq!(Color::from_hex("FF8000"), Some(Color::new(255, 128, 0)));
+        assert_eq!(Color::from_hex("#ff80"), None);
+        assert_eq!(Color::from_hex("zz8000"), None);
+    }
+
     #[test]
     fn test_yiq_conversion() {
         let dark_color = Color::new(50, 50, 50);
@@ -170,5 +308,30 @@ mod tests {
 
         assert!(dark_color.to_yiq() < light_color.to_yiq());
     }
-}
 
+    #[test]
+    fn test_hsl_roundtrip() {
+        for color in [
+            Color::new(255, 128, 0),
+            Color::new(12, 200, 180),
+            Color::new(90, 40, 160),
+            Color::new(128, 128, 128),
+        ] {
+            let (h, s, l) = color.to_hsl_components();
+            assert_eq!(Color::from_hsl(h, s, l), color);
+        }
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        let black = Color::new(0, 0, 0);
+        let white = Color::new(255, 255, 255);
+        assert!((black.contrast_ratio(white) - 21.0).abs() < 0.01);
+        assert!((white.contrast_ratio(black) - 21.0).abs() < 0.01);
+        assert_eq!(white.contrast_ratio(white), 1.0);
+
+        // #777777 on white is the classic just-below-AA grey
+        let grey = Color::new(0x77, 0x77, 0x77);
+        assert!((grey.contrast_ratio(white) - 4.48).abs() < 0.01);
+    }
+}
@@ -0,0 +1,190 @@
+//! The error type shared by every fallible walrus function
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Result with a [`WalrusError`]
+pub type Result<T> = std::result::Result<T, WalrusError>;
+
+/// Everything that can go wrong in walrus, each kind with its own exit code
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WalrusError {
+    /// An image could not be read or decoded, path is None for in-memory data
+    ImageDecode {
+        /// Image file, None for stdin and in-memory data
+        path: Option<PathBuf>,
+        /// Decoder error
+        source: image::ImageError,
+    },
+    /// The image is in a format this build of walrus can't decode
+    UnsupportedFormat {
+        /// Image file, None for in-memory data
+        path: Option<PathBuf>,
+        /// Name of the format, None when it wasn't recognised
+        format: Option<String>,
+    },
+    /// The image produced no colors to build a palette from
+    EmptyPalette,
+    /// A template could not be parsed
+    TemplateParse {
+        /// Template file
+        path: PathBuf,
+        /// What is wrong with it
+        message: String,
+    },
+    /// Reading or writing a file failed
+    Io {
+        /// File or directory, "-" for stdin and stdout
+        path: PathBuf,
+        /// Underlying error
+        source: io::Error,
+    },
+    /// Invalid arguments, themes or configuration
+    Config(String),
+    /// Sending escape sequences to a terminal failed
+    TerminalWrite {
+        /// Terminal device
+        path: PathBuf,
+        /// Underlying error
+        source: io::Error,
+    },
+    /// An external command (wallpaper setter, hook) could not run or failed
+    Command {
+        /// Command line
+        command: String,
+        /// Why it failed
+        message: String,
+    },
+}
+
+impl WalrusError {
+    /// Io error with the path it happened on
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        WalrusError::Io {
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// Process exit code, distinct per kind so wrapper scripts can tell them apart
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            WalrusError::Config(_) => 2,
+            WalrusError::ImageDecode { .. } => 3,
+            WalrusError::EmptyPalette => 4,
+            WalrusError::TemplateParse { .. } => 5,
+            WalrusError::Io { .. } => 6,
+            WalrusError::TerminalWrite { .. } => 7,
+            WalrusError::Command { .. } => 8,
+            WalrusError::UnsupportedFormat { .. } => 9,
+        }
+    }
+}
+
+impl fmt::Display for WalrusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalrusError::ImageDecode {
+                path: Some(path),
+                source,
+            } => write!(f, "Could not decode image {}: {}", path.display(), source),
+            WalrusError::ImageDecode { path: None, source } => {
+                write!(f, "Could not decode image: {}", source)
+            }
+            WalrusError::UnsupportedFormat { path, format } => {
+                if let Some(path) = path {
+                    write!(f, "{}: ", path.display())?;
+                }
+                match format {
+                    Some(format) => write!(
+                        f,
+                        "{} images are not supported by this build of walrus",
+                        format
+                    ),
+                    None => write!(f, "Unrecognised image format"),
+                }
+            }
+            WalrusError::EmptyPalette => write!(f, "No colors found in image"),
+            WalrusError::TemplateParse { path, message } => {
+                write!(f, "Invalid template {}: {}", path.display(), message)
+            }
+            WalrusError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            WalrusError::Config(message) => write!(f, "{}", message),
+            WalrusError::TerminalWrite { path, source } => {
+                write!(f, "Failed to write to {}: {}", path.display(), source)
+            }
+            WalrusError::Command { command, message } => write!(f, "{}: {}", command, message),
+        }
+    }
+}
+
+impl std::error::Error for WalrusError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WalrusError::ImageDecode { source, .. } => Some(source),
+            WalrusError::Io { source, .. } | WalrusError::TerminalWrite { source, .. } => {
+                Some(source)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for WalrusError {
+    fn from(message: String) -> Self {
+        WalrusError::Config(message)
+    }
+}
+
+impl From<&str> for WalrusError {
+    fn from(message: &str) -> Self {
+        WalrusError::Config(message.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let errors = [
+            WalrusError::Config("bad".into()),
+            WalrusError::ImageDecode {
+                path: None,
+                source: image::ImageError::IoError(io::ErrorKind::InvalidData.into()),
+            },
+            WalrusError::UnsupportedFormat {
+                path: None,
+                format: Some("AVIF".into()),
+            },
+            WalrusError::EmptyPalette,
+            WalrusError::TemplateParse {
+                path: "t".into(),
+                message: "bad".into(),
+            },
+            WalrusError::io("a", io::ErrorKind::NotFound.into()),
+            WalrusError::TerminalWrite {
+                path: "/dev/pts/0".into(),
+                source: io::ErrorKind::PermissionDenied.into(),
+            },
+            WalrusError::Command {
+                command: "swww".into(),
+                message: "exit status: 1".into(),
+            },
+        ];
+
+        let mut codes: Vec<u8> = errors.iter().map(WalrusError::exit_code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0) && !codes.contains(&1));
+
+        assert_eq!(
+            WalrusError::io("/tmp/colors.css", io::ErrorKind::NotFound.into()).to_string(),
+            "/tmp/colors.css: entity not found"
+        );
+    }
+}
@@ -1,32 +1,211 @@
+//! Turns an image into a 16 color terminal palette, and derives Base16/Base24 schemes
+
+use image::RgbaImage;
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
 use crate::color::Color;
-use crate::haishoku::Haishoku;
+use crate::error::{Result, WalrusError};
+use crate::haishoku::{self, Focus, FrameSampling, Haishoku, Region, ResizeFilter, Resolution};
+
+// Fractions of the way from background to foreground for base00-base07
+const BASE16_RAMP: [f32; 8] = [0.0, 0.08, 0.18, 0.38, 0.62, 0.82, 0.91, 1.0];
+
+// Target hues for the base16 accents base08-base0F
+const BASE16_ACCENT_HUES: [f32; 8] = [
+    0.0,   // base08 red
+    30.0,  // base09 orange
+    55.0,  // base0A yellow
+    120.0, // base0B green
+    180.0, // base0C cyan
+    220.0, // base0D blue
+    290.0, // base0E magenta
+    25.0,  // base0F brown
+];
+
+// Base24 bright accents base12-base17 are lifted from these base16 accents
+const BASE24_BRIGHT_SOURCES: [usize; 6] = [0x08, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E];
+
+// Palettes with fewer distinct hues in color1-6 get the rest synthesized, so
+// solid color, monochrome and black and white images still have usable accents
+const MIN_ACCENT_HUES: usize = 2;
+// Below this saturation a color counts as gray and has no hue
+const MIN_HUE_SATURATION: f32 = 0.15;
+// Hues closer than this, in degrees, count as the same hue
+const MIN_HUE_DISTANCE: f32 = 30.0;
+
+/// Every stage of the last generation, to tell which one made a palette look
+/// the way it does
+#[derive(Clone, Debug, Default)]
+pub struct Trace {
+    /// Weight and mean color of every color group, heaviest first
+    pub buckets: Vec<(f32, Color)>,
+    /// The groups kept after cutting to 8 and dropping similar colors, with the
+    /// fraction of the image they cover
+    pub palette: Vec<(f32, Color)>,
+    /// The palette sorted by YIQ luminance
+    pub sorted: Vec<Color>,
+    /// Each adjustment step, in order, with all 16 colors after it
+    pub steps: Vec<(&'static str, Vec<Color>)>,
+}
+
+impl Trace {
+    /// The trace as a JSON document, with a history of every color index
+    pub fn to_json(&self) -> String {
+        let weighted = |colors: &[(f32, Color)], key: &str| {
+            colors
+                .iter()
+                .map(|(weight, color)| {
+                    format!(
+                        "{{\"{}\": {}, \"color\": \"{}\"}}",
+                        key,
+                        weight,
+                        color.to_hex()
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let sorted: Vec<String> = self
+            .sorted
+            .iter()
+            .map(|color| format!("\"{}\"", color.to_hex()))
+            .collect();
+        let steps: Vec<String> = self
+            .steps
+            .iter()
+            .map(|(step, _)| format!("\"{}\"", step))
+            .collect();
+        let indices = self.steps.first().map_or(0, |(_, colors)| colors.len());
+        let colors: Vec<String> = (0..indices)
+            .map(|i| {
+                let history: Vec<String> = self
+                    .steps
+                    .iter()
+                    .map(|(step, colors)| format!("\"{}\": \"{}\"", step, colors[i].to_hex()))
+                    .collect();
+                format!("{{\"index\": {}, {}}}", i, history.join(", "))
+            })
+            .collect();
+
+        format!(
+            "{{\n  \"buckets\": {},\n  \"palette\": {},\n  \"sorted\": {},\n  \"steps\": {},\n  \"colors\": {}\n}}\n",
+            json_array(&weighted(&self.buckets, "weight")),
+            json_array(&weighted(&self.palette, "fraction")),
+            json_array(&sorted),
+            json_array(&steps),
+            json_array(&colors)
+        )
+    }
+}
+
+// One item per line, indented to sit in a top level key
+fn json_array(items: &[String]) -> String {
+    if items.is_empty() {
+        return "[]".to_string();
+    }
+    format!("[\n    {}\n  ]", items.join(",\n    "))
+}
 
+/// Builds pywal-style 16 color palettes from haishoku's dominant colors
 pub struct PywalGenerator {
     haishoku: Haishoku,
+    frame_sampling: FrameSampling,
+    focus: Focus,
+    saliency: bool,
+    filter: ResizeFilter,
+    resolution: Resolution,
+    region: Option<Region>,
+    monitors: u32,
+    monitor_schemes: Vec<Vec<Color>>,
+    timings: Vec<(&'static str, Duration)>,
+    trace: Trace,
+}
+
+impl Default for PywalGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PywalGenerator {
+    /// Generator with an empty haishoku state
     pub fn new() -> Self {
         PywalGenerator {
             haishoku: Haishoku::new(),
+            frame_sampling: FrameSampling::default(),
+            focus: Focus::Uniform,
+            saliency: false,
+            filter: ResizeFilter::Box,
+            resolution: Resolution::default(),
+            region: None,
+            monitors: 1,
+            monitor_schemes: Vec::new(),
+            timings: Vec::new(),
+            trace: Trace::default(),
         }
     }
 
-    fn gen_colors(&mut self, image_path: &str) -> Result<Vec<Color>, Box<dyn std::error::Error>> {
-        self.haishoku.load_haishoku(image_path)?;
+    /// Frames of animated images to build the palette from, 8 spread evenly by default
+    pub fn set_frame_sampling(&mut self, sampling: FrameSampling) {
+        self.frame_sampling = sampling;
+    }
 
-        let colors: Vec<Color> = self
-            .haishoku
-            .palette
-            .iter()
-            .map(|(_, color)| *color)
-            .collect();
-        Ok(colors)
+    /// Weight pixels towards the center or the edges of the image
+    pub fn set_focus(&mut self, focus: Focus) {
+        self.focus = focus;
+    }
+
+    /// Weight colorful, high contrast and central pixels more, see
+    /// Haishoku::set_saliency
+    pub fn set_saliency(&mut self, saliency: bool) {
+        self.saliency = saliency;
+    }
+
+    /// Filter to scale images down with before counting colors, box by default
+    pub fn set_filter(&mut self, filter: ResizeFilter) {
+        self.filter = filter;
+    }
+
+    /// Size to count colors at, a 256x256 thumbnail by default
+    pub fn set_resolution(&mut self, resolution: Resolution) {
+        self.resolution = resolution;
+    }
+
+    /// Only extract colors from this part of the image
+    pub fn set_region(&mut self, region: Region) {
+        self.region = Some(region);
+    }
+
+    /// Treat the image as a wallpaper spanning this many monitors side by side.
+    /// Each monitor counts the same towards the palette, and gets its own
+    /// scheme in monitor_schemes.
+    pub fn set_monitors(&mut self, monitors: u32) {
+        self.monitors = monitors.max(1);
+    }
+
+    /// One scheme per monitor from the last generation, left to right, empty
+    /// unless set_monitors was given more than one
+    pub fn monitor_schemes(&self) -> &[Vec<Color>] {
+        &self.monitor_schemes
+    }
+
+    /// How long each stage of the last generation took, in order: decode,
+    /// downscale, count and scheme
+    pub fn timings(&self) -> &[(&'static str, Duration)] {
+        &self.timings
+    }
+
+    /// Buckets, palette and adjustment steps of the last generation, empty
+    /// before the first
+    pub fn trace(&self) -> &Trace {
+        &self.trace
     }
 
-    fn adjust(&self, mut cols: Vec<Color>, light: bool) -> Vec<Color> {
+    fn adjust(&self, mut cols: Vec<Color>, light: bool, trace: &mut Trace) -> Vec<Color> {
         // Sort by YIQ (luminance) like pywal does
         cols.sort_by(|a, b| a.to_yiq().partial_cmp(&b.to_yiq()).unwrap());
+        trace.sorted = cols.clone();
 
         let mut raw_colors = Vec::new();
         raw_colors.extend_from_slice(&cols);
@@ -36,19 +215,91 @@ impl PywalGenerator {
             raw_colors.extend_from_slice(&cols);
         }
         raw_colors.truncate(16);
+        trace.steps.push(("repeat", raw_colors.clone()));
+
+        self.fill_accent_hues(&mut raw_colors);
+        trace.steps.push(("accent hues", raw_colors.clone()));
 
         if !raw_colors.is_empty() {
             raw_colors[0] = raw_colors[0].lighten(0.40);
         }
+        trace.steps.push(("lighten color0", raw_colors.clone()));
 
-        self.generic_adjust(raw_colors, light)
+        let adjusted = self.generic_adjust(raw_colors, light);
+        let step = if light { "light adjust" } else { "dark adjust" };
+        trace.steps.push((step, adjusted.clone()));
+        adjusted
+    }
+
+    fn fill_accent_hues(&self, colors: &mut [Color]) {
+        // Keep the accents that add a hue, the rest are candidates for replacement
+        let mut hues: Vec<f32> = Vec::new();
+        let mut chromatic: Vec<(f32, f32)> = Vec::new();
+        let mut duplicates = Vec::new();
+        for (i, color) in colors.iter().enumerate().take(7).skip(1) {
+            let (h, s, l) = color.to_hsl_components();
+            if s >= MIN_HUE_SATURATION {
+                chromatic.push((s, l));
+                if hues
+                    .iter()
+                    .all(|hue| hue_distance(*hue, h) >= MIN_HUE_DISTANCE)
+                {
+                    hues.push(h);
+                    continue;
+                }
+            }
+            duplicates.push(i);
+        }
+
+        if hues.len() >= MIN_ACCENT_HUES {
+            return;
+        }
+
+        // Synthesized accents share the image's saturation and lightness,
+        // gray images get muted ones
+        let (s, l) = if chromatic.is_empty() {
+            let l = colors[1..=6]
+                .iter()
+                .map(|color| color.to_hsl_components().2)
+                .sum::<f32>()
+                / 6.0;
+            (0.35, l)
+        } else {
+            let n = chromatic.len() as f32;
+            (
+                chromatic.iter().map(|(s, _)| s).sum::<f32>() / n,
+                chromatic.iter().map(|(_, l)| l).sum::<f32>() / n,
+            )
+        };
+        let s = s.clamp(0.35, 0.75);
+        let l = l.clamp(0.45, 0.65);
+
+        // Each new hue goes as far as possible from the ones already there
+        for i in duplicates {
+            let hue = (0..36)
+                .rev()
+                .map(|step| hues.first().copied().unwrap_or(0.0) + step as f32 * 10.0)
+                .max_by(|a, b| {
+                    let nearest = |h: f32| {
+                        hues.iter()
+                            .map(|hue| hue_distance(*hue, h))
+                            .fold(360.0, f32::min)
+                    };
+                    nearest(*a).partial_cmp(&nearest(*b)).unwrap()
+                })
+                .unwrap();
+            hues.push(hue.rem_euclid(360.0));
+            colors[i] = Color::from_hsl(hue, s, l);
+        }
+
+        // Bright variants match, like pywal with a full 8 color palette
+        colors.copy_within(1..7, 9);
     }
 
     fn generic_adjust(&self, mut colors: Vec<Color>, light: bool) -> Vec<Color> {
         if light {
-            for i in 0..colors.len() {
-                colors[i] = colors[i].saturate(0.60);
-                colors[i] = colors[i].darken(0.5);
+            for color in colors.iter_mut() {
+                *color = color.saturate(0.60).darken(0.5);
             }
 
             colors[0] = colors[0].lighten(0.95);
@@ -67,34 +318,404 @@ impl PywalGenerator {
 
     fn saturate_colors(&self, mut colors: Vec<Color>, amount: f32) -> Vec<Color> {
         if amount > 0.0 && amount <= 1.0 {
-            for i in 0..colors.len() {
+            for (i, color) in colors.iter_mut().enumerate() {
                 if i != 0 && i != 7 && i != 8 && i != 15 {
-                    colors[i] = colors[i].saturate(amount);
+                    *color = color.saturate(amount);
                 }
             }
         }
         colors
     }
 
+    /// Palette from an image file, saturation_factor 1.0 leaves colors untouched
     pub fn generate_from_image(
         &mut self,
         image_path: &str,
         saturation_factor: f32,
         light: bool,
-    ) -> Result<Vec<Color>, Box<dyn std::error::Error>> {
-        let cols = self.gen_colors(image_path)?;
+    ) -> Result<Vec<Color>> {
+        let start = Instant::now();
+        let frames = haishoku::open_frames(Path::new(image_path), self.frame_sampling)?;
+        self.timings = vec![("decode", start.elapsed())];
+        self.generate(&frames, saturation_factor, light)
+    }
+
+    /// Encoded image bytes, e.g. a screenshot piped in on stdin
+    pub fn generate_from_memory(
+        &mut self,
+        bytes: &[u8],
+        saturation_factor: f32,
+        light: bool,
+    ) -> Result<Vec<Color>> {
+        let start = Instant::now();
+        let frames = haishoku::decode_frames(bytes, self.frame_sampling)?;
+        self.timings = vec![("decode", start.elapsed())];
+        self.generate(&frames, saturation_factor, light)
+    }
+
+    /// Tightly packed RGB24 pixels
+    pub fn generate_from_rgb(
+        &mut self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        saturation_factor: f32,
+        light: bool,
+    ) -> Result<Vec<Color>> {
+        let start = Instant::now();
+        let image = haishoku::raw_rgb_image(pixels, width, height)?;
+        self.timings = vec![("decode", start.elapsed())];
+        self.generate(std::slice::from_ref(&image), saturation_factor, light)
+    }
+
+    fn generate(
+        &mut self,
+        frames: &[RgbaImage],
+        saturation_factor: f32,
+        light: bool,
+    ) -> Result<Vec<Color>> {
+        let cropped;
+        let frames = match self.region {
+            Some(region) => {
+                cropped = haishoku::crop_frames(frames, region)?;
+                &cropped[..]
+            }
+            None => frames,
+        };
+
+        let mut haishoku = Haishoku::new();
+        self.configure(&mut haishoku);
+        self.monitor_schemes.clear();
+        if self.monitors > 1 {
+            let mut parts = Vec::new();
+            for columns in haishoku::monitor_columns(frames, self.monitors) {
+                let mut part = Haishoku::new();
+                self.configure(&mut part);
+                part.load_haishoku_frames(&columns)?;
+                add_timings(&mut self.timings, part.timings());
+                let scheme = self.scheme(&part, saturation_factor, light, &mut Trace::default())?;
+                self.monitor_schemes.push(scheme);
+                parts.push(part);
+            }
+            haishoku.load_merged(&parts);
+        } else {
+            haishoku.load_haishoku_frames(frames)?;
+            add_timings(&mut self.timings, haishoku.timings());
+        }
+        self.haishoku = haishoku;
+
+        let start = Instant::now();
+        let mut trace = Trace {
+            buckets: self.haishoku.buckets().to_vec(),
+            palette: self.haishoku.palette.clone(),
+            ..Trace::default()
+        };
+        let scheme = self.scheme(&self.haishoku, saturation_factor, light, &mut trace);
+        self.trace = trace;
+        self.timings.push(("scheme", start.elapsed()));
+        scheme
+    }
+
+    // Haishoku with the extraction settings of this generator
+    fn configure(&self, haishoku: &mut Haishoku) {
+        haishoku.set_focus(self.focus);
+        haishoku.set_saliency(self.saliency);
+        haishoku.set_filter(self.filter);
+        haishoku.set_resolution(self.resolution);
+    }
+
+    // The 16 color scheme for an extracted palette
+    fn scheme(
+        &self,
+        haishoku: &Haishoku,
+        saturation_factor: f32,
+        light: bool,
+        trace: &mut Trace,
+    ) -> Result<Vec<Color>> {
+        let cols: Vec<Color> = haishoku.palette.iter().map(|(_, color)| *color).collect();
 
         if cols.is_empty() {
-            return Err("No colors found in image".into());
+            return Err(WalrusError::EmptyPalette);
         }
 
-        let mut adjusted_colors = self.adjust(cols, light);
+        let mut adjusted_colors = self.adjust(cols, light, trace);
 
         if saturation_factor != 1.0 {
             adjusted_colors = self.saturate_colors(adjusted_colors, saturation_factor);
+            trace.steps.push(("saturate", adjusted_colors.clone()));
         }
 
         Ok(adjusted_colors)
     }
+
+    /// Derive a Base16 scheme (base00-base0F) from a generated 16 color palette,
+    /// palettes without at least color0-color7 are an EmptyPalette error
+    pub fn generate_base16(&self, colors: &[Color]) -> Result<Vec<Color>> {
+        if colors.len() < 8 {
+            return Err(WalrusError::EmptyPalette);
+        }
+
+        let background = colors[0];
+        let foreground = *colors.get(15).unwrap_or(&colors[7]);
+        let light = background.to_yiq() > foreground.to_yiq();
+
+        // base00-base07 is a monotonic ramp from background to foreground
+        let mut scheme: Vec<Color> = BASE16_RAMP
+            .iter()
+            .map(|amount| background.mix(&foreground, *amount))
+            .collect();
+
+        let accents: Vec<(f32, f32, f32)> = colors
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !matches!(i, 0 | 7 | 8 | 15))
+            .map(|(_, color)| color.to_hsl_components())
+            .filter(|(_, s, _)| *s > 0.05)
+            .collect();
+
+        for (slot, target_hue) in BASE16_ACCENT_HUES.iter().enumerate() {
+            // Borrow saturation and lightness from the palette color closest in hue
+            let (_, s, l) = accents
+                .iter()
+                .min_by(|a, b| {
+                    hue_distance(a.0, *target_hue)
+                        .partial_cmp(&hue_distance(b.0, *target_hue))
+                        .unwrap()
+                })
+                .copied()
+                .unwrap_or((0.0, 0.5, 0.5));
+
+            let s = s.max(0.35);
+            let mut l = if light {
+                l.clamp(0.30, 0.45)
+            } else {
+                l.clamp(0.55, 0.72)
+            };
+
+            // base0F (brown) is a darker orange
+            if slot == 7 {
+                l *= 0.75;
+            }
+
+            scheme.push(Color::from_hsl(*target_hue, s, l));
+        }
+
+        Ok(scheme)
+    }
+
+    /// Derive a Base24 scheme: Base16 plus base10-base11 backgrounds and base12-base17 brights
+    pub fn generate_base24(&self, colors: &[Color]) -> Result<Vec<Color>> {
+        self.extend_base24(self.generate_base16(colors)?)
+    }
+
+    /// Add base10-base17 to an existing Base16 scheme, such as one from a theme,
+    /// schemes without all of base00-base0F are an EmptyPalette error
+    pub fn extend_base24(&self, mut scheme: Vec<Color>) -> Result<Vec<Color>> {
+        if scheme.len() < 16 {
+            return Err(WalrusError::EmptyPalette);
+        }
+        scheme.truncate(16);
+        let light = scheme[0].to_yiq() > scheme[7].to_yiq();
+
+        // Backgrounds further away from the foreground than base00
+        for amount in [0.3, 0.6] {
+            scheme.push(if light {
+                scheme[0].lighten(amount)
+            } else {
+                scheme[0].darken(amount)
+            });
+        }
+
+        for index in BASE24_BRIGHT_SOURCES {
+            scheme.push(if light {
+                scheme[index].darken(0.15)
+            } else {
+                scheme[index].lighten(0.2)
+            });
+        }
+
+        Ok(scheme)
+    }
+}
+
+// Add stage timings, summing stages that ran more than once
+fn add_timings(timings: &mut Vec<(&'static str, Duration)>, stages: &[(&'static str, Duration)]) {
+    for &(stage, duration) in stages {
+        match timings.iter_mut().find(|(name, _)| *name == stage) {
+            Some((_, total)) => *total += duration,
+            None => timings.push((stage, duration)),
+        }
+    }
 }
 
+fn hue_distance(a: f32, b: f32) -> f32 {
+    let distance = (a - b).abs() % 360.0;
+    distance.min(360.0 - distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base16_ramp_is_monotonic() {
+        let generator = PywalGenerator::new();
+        let theme = crate::theme::load_theme("gruvbox-dark").unwrap();
+
+        let scheme = generator.generate_base16(&theme).unwrap();
+        assert_eq!(scheme.len(), 16);
+        for pair in scheme[..8].windows(2) {
+            assert!(pair[0].to_yiq() <= pair[1].to_yiq());
+        }
+
+        let base24 = generator.generate_base24(&theme).unwrap();
+        assert_eq!(base24.len(), 24);
+        assert_eq!(base24[..16], scheme[..]);
+
+        assert!(matches!(
+            generator.generate_base16(&theme[..4]),
+            Err(WalrusError::EmptyPalette)
+        ));
+        assert!(matches!(
+            generator.generate_base24(&[]),
+            Err(WalrusError::EmptyPalette)
+        ));
+        assert!(matches!(
+            generator.extend_base24(theme[..15].to_vec()),
+            Err(WalrusError::EmptyPalette)
+        ));
+    }
+
+    fn distinct_hues(colors: &[Color]) -> usize {
+        let mut hues: Vec<f32> = Vec::new();
+        for (h, s, _) in colors.iter().map(|color| color.to_hsl_components()) {
+            if s >= MIN_HUE_SATURATION
+                && hues
+                    .iter()
+                    .all(|hue| hue_distance(*hue, h) >= MIN_HUE_DISTANCE)
+            {
+                hues.push(h);
+            }
+        }
+        hues.len()
+    }
+
+    #[test]
+    fn test_solid_color_image() {
+        let mut generator = PywalGenerator::new();
+        let pixels = [30u8, 90, 200].repeat(32 * 32);
+        let colors = generator
+            .generate_from_rgb(&pixels, 32, 32, 1.0, false)
+            .unwrap();
+
+        assert_eq!(colors.len(), 16);
+        assert_eq!(distinct_hues(&colors[1..7]), 6);
+        assert_eq!(colors[1..7], colors[9..15]);
+        assert!(colors[7].contrast_ratio(colors[0]) >= 4.5);
+        // The image's own hue is kept
+        assert!(colors[1..7].contains(&Color::new(30, 90, 200)));
+    }
+
+    #[test]
+    fn test_black_and_white_image() {
+        let mut generator = PywalGenerator::new();
+        let pixels: Vec<u8> = (0..32 * 32)
+            .flat_map(|i| {
+                if (i / 4) % 2 == 0 {
+                    [0u8; 3]
+                } else {
+                    [255u8; 3]
+                }
+            })
+            .collect();
+
+        for light in [false, true] {
+            let colors = generator
+                .generate_from_rgb(&pixels, 32, 32, 1.0, light)
+                .unwrap();
+            assert_eq!(distinct_hues(&colors[1..7]), 6);
+            for color in &colors[1..7] {
+                assert!(color.contrast_ratio(colors[0]) >= 3.0, "{}", color.to_hex());
+            }
+        }
+    }
+
+    #[test]
+    fn test_scheme_per_monitor() {
+        let mut generator = PywalGenerator::new();
+        let pixels: Vec<u8> = (0..64 * 32)
+            .flat_map(|i| {
+                if i % 64 < 32 {
+                    [200, 40, 40]
+                } else {
+                    [40, 60, 210]
+                }
+            })
+            .collect();
+
+        generator
+            .generate_from_rgb(&pixels, 64, 32, 1.0, false)
+            .unwrap();
+        assert!(generator.monitor_schemes().is_empty());
+
+        generator.set_monitors(2);
+        let merged = generator
+            .generate_from_rgb(&pixels, 64, 32, 1.0, false)
+            .unwrap();
+        let schemes = generator.monitor_schemes();
+        assert_eq!(schemes.len(), 2);
+        assert!(schemes[0].contains(&Color::new(200, 40, 40)));
+        assert!(!schemes[0].contains(&Color::new(40, 60, 210)));
+        assert!(schemes[1].contains(&Color::new(40, 60, 210)));
+        assert!(
+            merged.contains(&Color::new(200, 40, 40)) && merged.contains(&Color::new(40, 60, 210))
+        );
+    }
+
+    #[test]
+    fn test_varied_palette_is_untouched() {
+        let generator = PywalGenerator::new();
+        let mut colors: Vec<Color> = (0..16)
+            .map(|i| Color::from_hsl(i as f32 * 50.0, 0.6, 0.5))
+            .collect();
+        let original = colors.clone();
+
+        generator.fill_accent_hues(&mut colors);
+        assert_eq!(colors, original);
+    }
+
+    #[test]
+    fn test_trace_records_every_step() {
+        let image = RgbaImage::from_fn(64, 64, |x, y| {
+            image::Rgba([(x * 4) as u8, (y * 4) as u8, 128, 255])
+        });
+        let mut generator = PywalGenerator::new();
+        let colors = generator
+            .generate(std::slice::from_ref(&image), 0.5, false)
+            .unwrap();
+
+        let trace = generator.trace();
+        assert!(trace.buckets.len() >= trace.palette.len());
+        assert!(trace.buckets.windows(2).all(|pair| pair[0].0 >= pair[1].0));
+        assert_eq!(trace.sorted.len(), trace.palette.len());
+        let steps: Vec<&str> = trace.steps.iter().map(|(step, _)| *step).collect();
+        assert_eq!(
+            steps,
+            [
+                "repeat",
+                "accent hues",
+                "lighten color0",
+                "dark adjust",
+                "saturate"
+            ]
+        );
+        assert_eq!(trace.steps.last().unwrap().1, colors);
+
+        let json = trace.to_json();
+        assert!(json.contains(&format!(
+            "{{\"index\": 15, \"repeat\": \"{}\"",
+            trace.steps[0].1[15].to_hex()
+        )));
+        assert!(json.contains("\"steps\": [\n    \"repeat\",\n"));
+    }
+}
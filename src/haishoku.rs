@@ -1,149 +1,384 @@
+//! The haishoku dominant color algorithm, plus helpers to decode images and
+//! the frames of animated ones
+
 use crate::color::Color;
-use image;
-use std::collections::HashMap;
+use crate::error::{Result, WalrusError};
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+#[cfg(feature = "svg")]
+use image::error::{DecodingError, LimitError, LimitErrorKind};
+use image::error::{ImageFormatHint, ParameterError, ParameterErrorKind, UnsupportedErrorKind};
+use image::imageops::{self, FilterType};
+use image::{
+    AnimationDecoder, DynamicImage, Frame, ImageError, ImageFormat, ImageResult, RgbImage,
+    RgbaImage,
+};
+use std::borrow::Cow;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "parallel")]
+use image::GenericImageView;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+// Weighted pixel count and channel sums of one group of colors
+#[derive(Clone, Copy, Default)]
+struct Bucket {
+    count: u64,
+    r: u64,
+    g: u64,
+    b: u64,
+}
+
+// Colors grouped into a 3x3x3 grid over the RGB cube, red major
+type Histogram = [Bucket; 27];
 
+/// Which frames of an animated GIF, APNG or WebP feed the palette
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FrameSampling {
+    /// Up to this many frames, spread evenly over the animation
+    Evenly(usize),
+    /// Every frame
+    All,
+}
+
+impl Default for FrameSampling {
+    fn default() -> Self {
+        FrameSampling::Evenly(8)
+    }
+}
+
+/// Which part of the image counts most towards the palette
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Focus {
+    /// Every pixel counts the same
+    #[default]
+    Uniform,
+    /// Pixels count up to 4 times as much the closer they are to the center
+    Center,
+    /// Pixels count up to 4 times as much the closer they are to the edges
+    Edges,
+}
+
+/// A rectangle of an image in pixels
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+    /// Left edge
+    pub x: u32,
+    /// Top edge
+    pub y: u32,
+    /// Width
+    pub width: u32,
+    /// Height
+    pub height: u32,
+}
+
+/// How images are scaled down before their colors are counted
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ResizeFilter {
+    /// Every thumbnail pixel is the average of the pixels it covers
+    #[default]
+    Box,
+    /// Every thumbnail pixel is one pixel of the image, thin details survive
+    /// instead of being averaged away
+    Nearest,
+    /// Linear interpolation
+    Triangle,
+    /// Lanczos with a window of 3, the sharpest and slowest
+    Lanczos,
+}
+
+/// The size colors are counted at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Resolution {
+    /// A square thumbnail with sides of this many pixels
+    Thumbnail(u32),
+    /// Every pixel of the image, slow on large images
+    Full,
+}
+
+impl Default for Resolution {
+    fn default() -> Self {
+        Resolution::Thumbnail(256)
+    }
+}
+
+/// Dominant color extraction: pixels are grouped into a 3x3x3 grid over the
+/// RGB cube and the weighted mean of the largest groups forms the palette
 pub struct Haishoku {
+    /// Color of the largest group
     pub dominant: Option<Color>,
-    pub palette: Vec<(f32, Color)>, // (percentage, color)
+    /// Up to 8 colors with the fraction of the image they cover
+    pub palette: Vec<(f32, Color)>,
+    focus: Focus,
+    saliency: bool,
+    filter: ResizeFilter,
+    resolution: Resolution,
+    buckets: Vec<(f32, Color)>,
+    timings: Vec<(&'static str, Duration)>,
+}
+
+impl Default for Haishoku {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Haishoku {
+    /// Empty state, fill it with load_haishoku
     pub fn new() -> Self {
         Haishoku {
             dominant: None,
             palette: Vec::new(),
+            focus: Focus::Uniform,
+            saliency: false,
+            filter: ResizeFilter::Box,
+            resolution: Resolution::default(),
+            buckets: Vec::new(),
+            timings: Vec::new(),
         }
     }
 
-    pub fn load_haishoku(&mut self, image_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let colors_mean = self.get_colors_mean(image_path)?;
+    /// Weight pixels by where they are in the image, uniform by default
+    pub fn set_focus(&mut self, focus: Focus) {
+        self.focus = focus;
+    }
+
+    /// Let colorful, high contrast and central pixels count more, so a subject
+    /// isn't drowned out by large areas of sky or wall. Off by default.
+    pub fn set_saliency(&mut self, saliency: bool) {
+        self.saliency = saliency;
+    }
 
-        self.palette = self.calculate_palette(&colors_mean);
+    /// Filter to scale images down with, box by default
+    pub fn set_filter(&mut self, filter: ResizeFilter) {
+        self.filter = filter;
+    }
 
-        self.dominant = self.calculate_dominant(&colors_mean);
+    /// Size to count colors at, a 256x256 thumbnail by default
+    pub fn set_resolution(&mut self, resolution: Resolution) {
+        self.resolution = resolution;
+    }
 
-        Ok(())
+    /// Weight and mean color of every group of the last load, heaviest first,
+    /// before the palette is cut to 8 colors and similar colors are dropped
+    pub fn buckets(&self) -> &[(f32, Color)] {
+        &self.buckets
+    }
+
+    /// How long scaling down and counting colors took in the last load
+    pub fn timings(&self) -> &[(&'static str, Duration)] {
+        &self.timings
     }
 
-    fn get_colors(
-        &self,
-        image_path: &str,
-    ) -> Result<Vec<(u32, Color)>, Box<dyn std::error::Error>> {
-        let img = image::open(image_path)?;
-        let rgb_img = img.to_rgb8();
+    /// Extract the palette and dominant color of an image, fully transparent
+    /// pixels are left out
+    pub fn load_haishoku(&mut self, image: &RgbaImage) -> Result<()> {
+        self.load_haishoku_frames(std::slice::from_ref(image))
+    }
 
-        let thumbnail = image::imageops::thumbnail(&rgb_img, 256, 256);
+    /// Extract one palette from several frames, each weighted by its size
+    pub fn load_haishoku_frames(&mut self, frames: &[RgbaImage]) -> Result<()> {
+        let start = Instant::now();
+        let images = self.downscale_frames(frames);
+        let downscaled = Instant::now();
+        self.buckets = self.get_buckets(&images);
+        let colors_mean = self.get_colors_mean(&self.buckets);
+        self.timings = vec![
+            ("downscale", downscaled - start),
+            ("count", downscaled.elapsed()),
+        ];
 
-        let mut color_counts: HashMap<Color, u32> = HashMap::new();
+        self.palette = self.calculate_palette(&colors_mean);
 
-        for pixel in thumbnail.pixels() {
-            let color = Color::new(pixel[0], pixel[1], pixel[2]);
-            *color_counts.entry(color).or_insert(0) += 1;
-        }
+        self.dominant = self.calculate_dominant(&colors_mean);
+
+        Ok(())
+    }
 
-        let image_colors: Vec<(u32, Color)> = color_counts
-            .into_iter()
-            .map(|(color, count)| (count, color))
+    /// Combine the palettes of several regions, e.g. the monitors a wallpaper
+    /// spans, each counting the same however large it is
+    pub fn load_merged(&mut self, parts: &[Haishoku]) {
+        let share = 1.0 / parts.len().max(1) as f32;
+        let mut colors_mean: Vec<(f32, Color)> = parts
+            .iter()
+            .flat_map(|part| {
+                part.palette
+                    .iter()
+                    .map(move |(fraction, color)| (fraction * share, *color))
+            })
             .collect();
+        colors_mean.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        self.buckets = colors_mean.clone();
+
+        colors_mean = self.filter_similar_colors(colors_mean);
+        colors_mean.truncate(8);
 
-        Ok(image_colors)
+        self.palette = self.calculate_palette(&colors_mean);
+        self.dominant = self.calculate_dominant(&colors_mean);
     }
 
-    fn sort_by_rgb(&self, image_colors: Vec<(u32, Color)>) -> Vec<(u32, Color)> {
-        let mut sorted = image_colors;
-        sorted.sort_by(|a, b| {
-            a.1.r
-                .cmp(&b.1.r)
-                .then_with(|| a.1.g.cmp(&b.1.g))
-                .then_with(|| a.1.b.cmp(&b.1.b))
-        });
-        sorted
-    }
-
-    fn group_by_accuracy(
-        &self,
-        sorted_colors: Vec<(u32, Color)>,
-    ) -> Vec<Vec<Vec<Vec<(u32, Color)>>>> {
-        let mut groups = Vec::new();
-        for _ in 0..3 {
-            let mut layer = Vec::new();
-            for _ in 0..3 {
-                let mut row = Vec::new();
-                for _ in 0..3 {
-                    row.push(Vec::new());
-                }
-                layer.push(row);
+    // How many times a pixel counts, 1-4 depending on the focus
+    fn pixel_weight(&self, x: u32, y: u32, width: u32, height: u32) -> u32 {
+        match self.focus {
+            Focus::Uniform => 1,
+            Focus::Center => {
+                1 + (3.0 * (1.0 - center_distance(x, y, width, height))).round() as u32
             }
-            groups.push(layer);
+            Focus::Edges => 1 + (3.0 * center_distance(x, y, width, height)).round() as u32,
         }
+    }
 
-        for (count, color) in sorted_colors {
-            let r_group = (color.r as f32 / 255.0 * 2.99) as usize;
-            let g_group = (color.g as f32 / 255.0 * 2.99) as usize;
-            let b_group = (color.b as f32 / 255.0 * 2.99) as usize;
-
-            groups[r_group][g_group][b_group].push((count, color));
-        }
+    // Frames at the size their colors are counted at, empty ones left out
+    fn downscale_frames<'a>(&self, frames: &'a [RgbaImage]) -> Vec<Cow<'a, RgbaImage>> {
+        #[cfg(feature = "parallel")]
+        let frames = frames.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let frames = frames.iter();
 
-        groups
+        frames
+            .filter(|frame| frame.width() > 0 && frame.height() > 0)
+            .map(|frame| self.downscale(frame))
+            .collect()
     }
 
-    pub fn get_weighted_mean(&self, grouped_colors: Vec<(u32, Color)>) -> (f32, Color) {
-        if grouped_colors.is_empty() {
-            return (0.0, Color::new(0, 0, 0));
-        }
+    // Scaling happens premultiplied, so transparent pixels don't bleed into
+    // their neighbours: their RGB values are arbitrary, often black
+    fn downscale<'a>(&self, image: &'a RgbaImage) -> Cow<'a, RgbaImage> {
+        let size = match self.resolution {
+            Resolution::Full => return Cow::Borrowed(image),
+            Resolution::Thumbnail(size) => size.max(1),
+        };
 
-        let mut total_count = 0u32;
-        let mut weighted_r = 0f32;
-        let mut weighted_g = 0f32;
-        let mut weighted_b = 0f32;
+        // Nearest neighbour doesn't blend pixels
+        let premultiply =
+            self.filter != ResizeFilter::Nearest && image.pixels().any(|pixel| pixel[3] < 255);
+        let premultiplied;
+        let source = if premultiply {
+            let mut pixels = image.clone();
+            #[cfg(feature = "parallel")]
+            let chunks = pixels.par_chunks_mut(4);
+            #[cfg(not(feature = "parallel"))]
+            let chunks = pixels.chunks_mut(4);
+            chunks.for_each(|pixel| {
+                let alpha = pixel[3] as u32;
+                for channel in &mut pixel[..3] {
+                    *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+                }
+            });
+            premultiplied = pixels;
+            &premultiplied
+        } else {
+            image
+        };
 
-        for (count, color) in &grouped_colors {
-            total_count += count;
-            weighted_r += (*count as f32) * (color.r as f32);
-            weighted_g += (*count as f32) * (color.g as f32);
-            weighted_b += (*count as f32) * (color.b as f32);
+        let mut thumbnail = match self.filter {
+            ResizeFilter::Box => scale_down(source, size),
+            ResizeFilter::Nearest => imageops::resize(source, size, size, FilterType::Nearest),
+            ResizeFilter::Triangle => imageops::resize(source, size, size, FilterType::Triangle),
+            ResizeFilter::Lanczos => imageops::resize(source, size, size, FilterType::Lanczos3),
+        };
+        if premultiply {
+            for pixel in thumbnail.pixels_mut().filter(|pixel| pixel[3] > 0) {
+                let alpha = pixel[3] as u32;
+                for channel in &mut pixel.0[..3] {
+                    *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+                }
+            }
         }
+        Cow::Owned(thumbnail)
+    }
 
-        let mean_r = (weighted_r / total_count as f32) as u8;
-        let mean_g = (weighted_g / total_count as f32) as u8;
-        let mean_b = (weighted_b / total_count as f32) as u8;
+    fn histogram(&self, images: &[Cow<RgbaImage>]) -> Histogram {
+        #[cfg(feature = "parallel")]
+        let histogram = images
+            .par_iter()
+            .map(|image| self.image_histogram(image))
+            .reduce(|| [Bucket::default(); 27], merge_histograms);
+        #[cfg(not(feature = "parallel"))]
+        let histogram = images
+            .iter()
+            .map(|image| self.image_histogram(image))
+            .fold([Bucket::default(); 27], merge_histograms);
 
-        (total_count as f32, Color::new(mean_r, mean_g, mean_b))
+        histogram
     }
 
-    fn get_colors_mean(
-        &self,
-        image_path: &str,
-    ) -> Result<Vec<(f32, Color)>, Box<dyn std::error::Error>> {
-        let image_colors = self.get_colors(image_path)?;
-
-        let sorted_image_colors = self.sort_by_rgb(image_colors);
+    fn image_histogram(&self, image: &RgbaImage) -> Histogram {
+        let saliency = self.saliency.then(|| saliency_weights(image));
+        let (width, height) = image.dimensions();
 
-        let grouped_image_colors = self.group_by_accuracy(sorted_image_colors);
+        let row_histogram = |(y, row): (usize, &[u8])| {
+            let mut histogram = [Bucket::default(); 27];
+            for (x, pixel) in row.chunks_exact(4).enumerate() {
+                if pixel[3] == 0 {
+                    continue;
+                }
 
-        let mut colors_mean = Vec::new();
-        for i in 0..3 {
-            for j in 0..3 {
-                for k in 0..3 {
-                    let grouped_color = &grouped_image_colors[i][j][k];
-                    if !grouped_color.is_empty() {
-                        let color_mean = self.get_weighted_mean(grouped_color.clone());
-                        colors_mean.push(color_mean);
-                    }
+                let mut weight = self.pixel_weight(x as u32, y as u32, width, height) as u64;
+                if let Some(saliency) = &saliency {
+                    weight *= saliency[y * width as usize + x] as u64;
                 }
+                let bucket = &mut histogram[bucket_index(pixel[0], pixel[1], pixel[2])];
+                bucket.count += weight;
+                bucket.r += weight * pixel[0] as u64;
+                bucket.g += weight * pixel[1] as u64;
+                bucket.b += weight * pixel[2] as u64;
             }
-        }
+            histogram
+        };
 
-        colors_mean.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        let rows = image.as_raw();
+        let row_length = width as usize * 4;
+        #[cfg(feature = "parallel")]
+        let histogram = rows
+            .par_chunks(row_length)
+            .enumerate()
+            .map(row_histogram)
+            .reduce(|| [Bucket::default(); 27], merge_histograms);
+        #[cfg(not(feature = "parallel"))]
+        let histogram = rows
+            .chunks(row_length)
+            .enumerate()
+            .map(row_histogram)
+            .fold([Bucket::default(); 27], merge_histograms);
 
-        if colors_mean.len() > 8 {
-            colors_mean.truncate(8);
-        }
+        histogram
+    }
 
-        colors_mean = self.filter_similar_colors(colors_mean);
+    // Weighted mean color of every group that has pixels, heaviest first
+    fn get_buckets(&self, images: &[Cow<RgbaImage>]) -> Vec<(f32, Color)> {
+        let mut buckets: Vec<(f32, Color)> = self
+            .histogram(images)
+            .iter()
+            .filter(|bucket| bucket.count > 0)
+            .map(|bucket| {
+                let mean = |sum: u64| (sum / bucket.count) as u8;
+                (
+                    bucket.count as f32,
+                    Color::new(mean(bucket.r), mean(bucket.g), mean(bucket.b)),
+                )
+            })
+            .collect();
+
+        buckets.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        buckets
+    }
+
+    // The 8 heaviest buckets, less those similar to a heavier one
+    fn get_colors_mean(&self, buckets: &[(f32, Color)]) -> Vec<(f32, Color)> {
+        let mut colors_mean = buckets.to_vec();
+        colors_mean.truncate(8);
 
-        Ok(colors_mean)
+        self.filter_similar_colors(colors_mean)
     }
 
     fn filter_similar_colors(&self, colors: Vec<(f32, Color)>) -> Vec<(f32, Color)> {
@@ -202,37 +437,449 @@ impl Haishoku {
         palette
     }
 }
+
+// Longest side of rasterized SVGs, in pixels
+#[cfg(feature = "svg")]
+const SVG_SIZE: f32 = 1024.0;
+
+fn bucket_index(r: u8, g: u8, b: u8) -> usize {
+    let group = |channel: u8| (channel as f32 / 255.0 * 2.99) as usize;
+    group(r) * 9 + group(g) * 3 + group(b)
+}
+
+fn merge_histograms(mut histogram: Histogram, other: Histogram) -> Histogram {
+    for (bucket, other) in histogram.iter_mut().zip(other) {
+        bucket.count += other.count;
+        bucket.r += other.r;
+        bucket.g += other.g;
+        bucket.b += other.b;
+    }
+    histogram
+}
+
+#[cfg(not(feature = "parallel"))]
+fn scale_down(image: &RgbaImage, size: u32) -> RgbaImage {
+    imageops::thumbnail(image, size, size)
+}
+
+// When shrinking, every row of imageops::thumbnail only reads its own band of
+// source rows, so the bands can be scaled on their own and stacked with the
+// same result
+#[cfg(feature = "parallel")]
+fn scale_down(image: &RgbaImage, size: u32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    if height < size {
+        return imageops::thumbnail(image, size, size);
+    }
+
+    // Same band bounds as imageops::thumbnail
+    let y_ratio = height as f32 / size as f32;
+    let pixels: Vec<u8> = (0..size)
+        .into_par_iter()
+        .flat_map_iter(|y| {
+            let bottomf = y as f32 * y_ratio;
+            let bottom = (bottomf.ceil() as u32).min(height - 1);
+            let top = ((bottomf + y_ratio).ceil() as u32).clamp(bottom, height);
+            let band = image.view(0, bottom, width, top - bottom);
+            imageops::thumbnail(&*band, size, 1).into_raw()
+        })
+        .collect();
+
+    RgbaImage::from_raw(size, size, pixels).unwrap()
+}
+
+// 0.0 in the center of a width x height image, 1.0 in the corners
+fn center_distance(x: u32, y: u32, width: u32, height: u32) -> f32 {
+    let dx = (x as f32 + 0.5) / width as f32 * 2.0 - 1.0;
+    let dy = (y as f32 + 0.5) / height as f32 * 2.0 - 1.0;
+    ((dx * dx + dy * dy) / 2.0).sqrt()
+}
+
+// A simple saliency heuristic: how colorful a pixel is or how much its
+// luminance differs from its neighbours, whichever is more, toned down away
+// from the center. Flat gray areas weigh 1, vivid central details up to 8.
+fn saliency_weights(image: &RgbaImage) -> Vec<u32> {
+    let (width, height) = image.dimensions();
+    let luma: Vec<f32> = image
+        .pixels()
+        .map(|pixel| {
+            (0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32) / 255.0
+        })
+        .collect();
+    let luma_at = |x: u32, y: u32| luma[(y * width + x) as usize];
+
+    image
+        .enumerate_pixels()
+        .map(|(x, y, pixel)| {
+            let chroma = (pixel.0[..3].iter().max().unwrap() - pixel.0[..3].iter().min().unwrap())
+                as f32
+                / 255.0;
+
+            let neighbours = [
+                (x.saturating_sub(1), y),
+                ((x + 1).min(width - 1), y),
+                (x, y.saturating_sub(1)),
+                (x, (y + 1).min(height - 1)),
+            ];
+            let gradient = neighbours
+                .iter()
+                .map(|&(nx, ny)| (luma_at(nx, ny) - luma_at(x, y)).abs())
+                .fold(0.0, f32::max);
+            // A quarter of the luminance range already counts as a hard edge
+            let contrast = (gradient * 4.0).min(1.0);
+
+            let center = 1.0 - center_distance(x, y, width, height);
+
+            1 + (7.0 * chroma.max(contrast) * (0.5 + 0.5 * center)).round() as u32
+        })
+        .collect()
+}
+
+/// The region of every frame, clipped to the image
+pub fn crop_frames(frames: &[RgbaImage], region: Region) -> Result<Vec<RgbaImage>> {
+    frames
+        .iter()
+        .map(|frame| {
+            if region.x >= frame.width()
+                || region.y >= frame.height()
+                || region.width == 0
+                || region.height == 0
+            {
+                return Err(WalrusError::Config(format!(
+                    "Region {},{},{},{} is outside the {}x{} image",
+                    region.x,
+                    region.y,
+                    region.width,
+                    region.height,
+                    frame.width(),
+                    frame.height()
+                )));
+            }
+            Ok(
+                imageops::crop_imm(frame, region.x, region.y, region.width, region.height)
+                    .to_image(),
+            )
+        })
+        .collect()
+}
+
+/// Split every frame into equal columns, one per monitor of a spanned wallpaper
+pub fn monitor_columns(frames: &[RgbaImage], monitors: u32) -> Vec<Vec<RgbaImage>> {
+    (0..monitors)
+        .map(|monitor| {
+            frames
+                .iter()
+                .map(|frame| {
+                    let left = monitor * frame.width() / monitors;
+                    let right = (monitor + 1) * frame.width() / monitors;
+                    imageops::crop_imm(frame, left, 0, right - left, frame.height()).to_image()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Open and decode an image file, animated images give their first frame
+pub fn open_image(path: &Path) -> Result<RgbaImage> {
+    let bytes = fs::read(path).map_err(|e| WalrusError::io(path, e))?;
+    decode_with(&bytes, Some(path)).map_err(|source| decode_error(Some(path), source))
+}
+
+/// Decode an encoded image (PNG, JPEG, ...) held in memory, sniffing the format
+pub fn decode_image(bytes: &[u8]) -> Result<RgbaImage> {
+    decode_with(bytes, None).map_err(|source| decode_error(None, source))
+}
+
+/// Open an image file, sampling the frames of animated GIFs, APNGs and WebPs
+pub fn open_frames(path: &Path, sampling: FrameSampling) -> Result<Vec<RgbaImage>> {
+    let bytes = fs::read(path).map_err(|e| WalrusError::io(path, e))?;
+    decode_frames_with(&bytes, Some(path), sampling)
+        .map_err(|source| decode_error(Some(path), source))
+}
+
+/// Decode an encoded image held in memory, sampling the frames of animated ones
+pub fn decode_frames(bytes: &[u8], sampling: FrameSampling) -> Result<Vec<RgbaImage>> {
+    decode_frames_with(bytes, None, sampling).map_err(|source| decode_error(None, source))
+}
+
+// Formats the image crate can't decode in this build get their own error
+fn decode_error(path: Option<&Path>, source: ImageError) -> WalrusError {
+    let kind = match &source {
+        ImageError::Unsupported(error) => Some(error.kind()),
+        _ => None,
+    };
+    let Some(UnsupportedErrorKind::Format(hint)) = kind else {
+        return WalrusError::ImageDecode {
+            path: path.map(Path::to_path_buf),
+            source,
+        };
+    };
+
+    // A decoder that is compiled in but can't handle this file is a decode error
+    let known_format = match &hint {
+        ImageFormatHint::Exact(format) => Some(*format),
+        ImageFormatHint::PathExtension(extension) => ImageFormat::from_extension(extension),
+        _ => None,
+    };
+    if known_format.is_some_and(can_decode) {
+        return WalrusError::ImageDecode {
+            path: path.map(Path::to_path_buf),
+            source,
+        };
+    }
+
+    let format = match hint {
+        ImageFormatHint::Exact(format) => Some(format!("{:?}", format).to_uppercase()),
+        ImageFormatHint::Name(name) => Some(name),
+        ImageFormatHint::PathExtension(extension) => {
+            Some(extension.to_string_lossy().to_uppercase())
+        }
+        // Formats image doesn't know at all, such as HEIC, go by the extension
+        _ => path
+            .and_then(Path::extension)
+            .map(|extension| extension.to_string_lossy().to_uppercase()),
+    };
+
+    WalrusError::UnsupportedFormat {
+        path: path.map(Path::to_path_buf),
+        format,
+    }
+}
+
+// Whether this build has a decoder for the format. image 0.24 ties AVIF reading
+// to its encoder feature and reports DDS as unreadable although it loads it.
+fn can_decode(format: ImageFormat) -> bool {
+    match format {
+        ImageFormat::Avif => cfg!(feature = "avif"),
+        ImageFormat::Dds => true,
+        format => format.reading_enabled(),
+    }
+}
+
+// The contents decide the format, the extension covers formats without a signature
+fn sniff_format(bytes: &[u8], path: Option<&Path>) -> Option<ImageFormat> {
+    image::guess_format(bytes)
+        .ok()
+        .or_else(|| path.and_then(|path| ImageFormat::from_path(path).ok()))
+}
+
+fn is_svg(bytes: &[u8], path: Option<&Path>) -> bool {
+    let extension = path
+        .and_then(Path::extension)
+        .map(|extension| extension.to_ascii_lowercase());
+    if extension.is_some_and(|extension| extension == "svg" || extension == "svgz") {
+        return true;
+    }
+
+    // An XML prolog or comment may come before the root element
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    head.starts_with("<svg") || (head.starts_with("<?xml") && head.contains("<svg"))
+}
+
+fn decode_with(bytes: &[u8], path: Option<&Path>) -> ImageResult<RgbaImage> {
+    if is_svg(bytes, path) {
+        return rasterize_svg(bytes);
+    }
+
+    let image = match sniff_format(bytes, path) {
+        Some(format) => image::load_from_memory_with_format(bytes, format)?,
+        None => image::load_from_memory(bytes)?,
+    };
+    Ok(image.into_rgba8())
+}
+
+#[cfg(feature = "svg")]
+fn rasterize_svg(bytes: &[u8]) -> ImageResult<RgbaImage> {
+    use resvg::{tiny_skia, usvg};
+
+    let tree = usvg::Tree::from_data(bytes, &usvg::Options::default()).map_err(|e| {
+        ImageError::Decoding(DecodingError::new(ImageFormatHint::Name("SVG".into()), e))
+    })?;
+
+    let size = tree.size();
+    let scale = SVG_SIZE / size.width().max(size.height());
+    let width = ((size.width() * scale).ceil() as u32).max(1);
+    let height = ((size.height() * scale).ceil() as u32).max(1);
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| ImageError::Limits(LimitError::from_kind(LimitErrorKind::DimensionError)))?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let pixel = pixel.demultiply();
+            [pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()]
+        })
+        .collect();
+    Ok(RgbaImage::from_raw(width, height, pixels).expect("pixmap holds width * height pixels"))
+}
+
+#[cfg(not(feature = "svg"))]
+fn rasterize_svg(_bytes: &[u8]) -> ImageResult<RgbaImage> {
+    let hint = ImageFormatHint::Name("SVG".into());
+    Err(ImageError::Unsupported(
+        image::error::UnsupportedError::from_format_and_kind(
+            hint.clone(),
+            UnsupportedErrorKind::Format(hint),
+        ),
+    ))
+}
+
+fn decode_frames_with(
+    bytes: &[u8],
+    path: Option<&Path>,
+    sampling: FrameSampling,
+) -> ImageResult<Vec<RgbaImage>> {
+    if is_svg(bytes, path) {
+        return Ok(vec![rasterize_svg(bytes)?]);
+    }
+
+    match sniff_format(bytes, path) {
+        Some(ImageFormat::Gif) => {
+            sample_frames(GifDecoder::new(Cursor::new(bytes))?.into_frames(), sampling)
+        }
+        Some(ImageFormat::Png) => {
+            let decoder = PngDecoder::new(Cursor::new(bytes))?;
+            if decoder.is_apng() {
+                sample_frames(decoder.apng().into_frames(), sampling)
+            } else {
+                Ok(vec![DynamicImage::from_decoder(decoder)?.into_rgba8()])
+            }
+        }
+        Some(ImageFormat::WebP) => {
+            let decoder = WebPDecoder::new(Cursor::new(bytes))?;
+            if decoder.has_animation() {
+                sample_frames(decoder.into_frames(), sampling)
+            } else {
+                Ok(vec![DynamicImage::from_decoder(decoder)?.into_rgba8()])
+            }
+        }
+        _ => Ok(vec![decode_with(bytes, path)?]),
+    }
+}
+
+// Frames are composited in order so all of them get decoded, but only a bounded
+// number is kept: once there are twice as many as needed every other one is
+// dropped, and from then on only every 2nd, 4th, ... frame is kept
+fn sample_frames(
+    frames: impl Iterator<Item = ImageResult<Frame>>,
+    sampling: FrameSampling,
+) -> ImageResult<Vec<RgbaImage>> {
+    let limit = match sampling {
+        FrameSampling::Evenly(limit) => limit.max(1),
+        FrameSampling::All => {
+            return frames.map(|frame| Ok(frame?.into_buffer())).collect();
+        }
+    };
+
+    let mut kept: Vec<(usize, RgbaImage)> = Vec::new();
+    let mut stride = 1;
+    for (index, frame) in frames.enumerate() {
+        let frame = frame?;
+        if index % stride == 0 {
+            kept.push((index, frame.into_buffer()));
+        }
+        if kept.len() > 2 * limit {
+            stride *= 2;
+            kept.retain(|(index, _)| index % stride == 0);
+        }
+    }
+
+    if kept.len() <= limit {
+        return Ok(kept.into_iter().map(|(_, frame)| frame).collect());
+    }
+
+    // The middle frame of each of limit equal slices
+    let picks: Vec<usize> = (0..limit)
+        .map(|slice| (2 * slice + 1) * kept.len() / (2 * limit))
+        .collect();
+    Ok(kept
+        .into_iter()
+        .enumerate()
+        .filter(|(position, _)| picks.contains(position))
+        .map(|(_, (_, frame))| frame)
+        .collect())
+}
+
+/// Wrap a buffer of tightly packed RGB24 pixels, row by row, as an opaque image
+pub fn raw_rgb_image(pixels: &[u8], width: u32, height: u32) -> Result<RgbaImage> {
+    let expected = width as usize * height as usize * 3;
+    let image = if pixels.len() == expected {
+        RgbImage::from_raw(width, height, pixels.to_vec())
+    } else {
+        None
+    };
+
+    image
+        .map(|image| DynamicImage::ImageRgb8(image).into_rgba8())
+        .ok_or_else(|| WalrusError::ImageDecode {
+            path: None,
+            source: ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::Generic(
+                format!(
+                    "raw RGB buffer for {}x{} must be {} bytes, got {}",
+                    width,
+                    height,
+                    expected,
+                    pixels.len()
+                ),
+            ))),
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_weighted_mean() {
-        let haishoku = Haishoku::new();
-        let colors = vec![
-            (10, Color::new(255, 0, 0)),
-            (5, Color::new(0, 255, 0)),
-            (3, Color::new(0, 0, 255)),
-        ];
+        let mut haishoku = Haishoku::new();
+        haishoku.set_resolution(Resolution::Full);
+        // 10, 5 and 3 pixels of three reds that share a group
+        let image = RgbaImage::from_fn(18, 1, |x, _| match x {
+            0..10 => image::Rgba([200, 10, 10, 255]),
+            10..15 => image::Rgba([180, 30, 10, 255]),
+            _ => image::Rgba([190, 20, 30, 255]),
+        });
 
-        let (weight, mean_color) = haishoku.get_weighted_mean(colors);
-        assert_eq!(weight, 18.0);
-        assert!(mean_color.r > mean_color.g && mean_color.r > mean_color.b);
+        let buckets = haishoku.get_buckets(&[Cow::Borrowed(&image)]);
+        assert_eq!(buckets, vec![(18.0, Color::new(192, 17, 13))]);
     }
 
     #[test]
     fn test_color_grouping() {
         let haishoku = Haishoku::new();
-        let colors = vec![
-            (1, Color::new(255, 255, 255)), // Should go to group [2][2][2]
-            (1, Color::new(0, 0, 0)),       // Should go to group [0][0][0]
-            (1, Color::new(127, 127, 127)), // Should go to group [1][1][1]
-        ];
+        // Thirds of white, black and gray
+        let image = RgbaImage::from_fn(768, 256, |x, _| match x / 256 {
+            0 => image::Rgba([255, 255, 255, 255]), // group [2][2][2]
+            1 => image::Rgba([0, 0, 0, 255]),       // group [0][0][0]
+            _ => image::Rgba([127, 127, 127, 255]), // group [1][1][1]
+        });
+
+        let histogram = haishoku.histogram(&[Cow::Borrowed(&image)]);
+        let filled: Vec<usize> = (0..27).filter(|&i| histogram[i].count > 0).collect();
+        assert_eq!(filled, vec![0, 13, 26]);
+        assert_eq!(histogram[13].r / histogram[13].count, 127);
+    }
 
-        let groups = haishoku.group_by_accuracy(colors);
-        assert!(!groups[0][0][0].is_empty());
-        assert!(!groups[1][1][1].is_empty());
-        assert!(!groups[2][2][2].is_empty());
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_thumbnail_matches() {
+        for (width, height) in [(1000, 777), (300, 256), (640, 3000), (100, 100)] {
+            let image = RgbaImage::from_fn(width, height, |x, y| {
+                let noise = (x * 7919 + y * 104_729) % 251;
+                image::Rgba([noise as u8, (x % 256) as u8, (y % 256) as u8, 255])
+            });
+            assert_eq!(
+                scale_down(&image, 256),
+                imageops::thumbnail(&image, 256, 256)
+            );
+        }
     }
 
     #[test]
@@ -245,5 +892,387 @@ mod tests {
         assert_eq!(haishoku.color_distance(&color1, &color2), 0.0);
         assert!(haishoku.color_distance(&color1, &color3) > 0.0);
     }
-}
 
+    #[test]
+    fn test_raw_rgb_input() {
+        let pixels: Vec<u8> = [200u8, 30, 30].repeat(64 * 64);
+        let image = raw_rgb_image(&pixels, 64, 64).unwrap();
+
+        let mut haishoku = Haishoku::new();
+        haishoku.load_haishoku(&image).unwrap();
+        assert_eq!(haishoku.dominant, Some(Color::new(200, 30, 30)));
+
+        assert!(raw_rgb_image(&pixels, 64, 65).is_err());
+    }
+
+    #[test]
+    fn test_decode_image_sniffs_format() {
+        let image = RgbImage::from_pixel(8, 8, image::Rgb([10, 120, 240]));
+        let mut png = std::io::Cursor::new(Vec::new());
+        image
+            .write_to(&mut png, image::ImageOutputFormat::Png)
+            .unwrap();
+
+        let decoded = decode_image(png.get_ref()).unwrap();
+        assert_eq!(decoded.get_pixel(3, 3), &image::Rgba([10, 120, 240, 255]));
+        assert!(decode_image(b"not an image").is_err());
+    }
+
+    #[test]
+    fn test_transparent_pixels_are_ignored() {
+        // A red sticker on a transparent canvas, black under the alpha. The
+        // canvas gets scaled up, so its edges blend with the transparent part.
+        let image = RgbaImage::from_fn(40, 40, |x, y| {
+            if x < 10 && y < 10 {
+                image::Rgba([220, 40, 40, 255])
+            } else {
+                image::Rgba([0, 0, 0, 0])
+            }
+        });
+
+        let mut haishoku = Haishoku::new();
+        haishoku.load_haishoku(&image).unwrap();
+        // Only the sticker, give or take rounding at its edges
+        assert_eq!(haishoku.palette.len(), 1);
+        let dominant = haishoku.dominant.unwrap();
+        assert!(haishoku.color_distance(&dominant, &Color::new(220, 40, 40)) < 3.0);
+
+        let invisible = RgbaImage::from_pixel(8, 8, image::Rgba([255, 255, 255, 0]));
+        haishoku.load_haishoku(&invisible).unwrap();
+        assert!(haishoku.palette.is_empty());
+        assert_eq!(haishoku.dominant, None);
+    }
+
+    fn solid_frame(value: u8) -> Frame {
+        Frame::new(RgbaImage::from_pixel(4, 4, image::Rgba([value, 0, 0, 255])))
+    }
+
+    #[test]
+    fn test_frame_sampling_spreads_evenly() {
+        let frames = (0..50).map(|i| Ok(solid_frame(i)));
+        let sampled = sample_frames(frames, FrameSampling::Evenly(4)).unwrap();
+        let indices: Vec<u8> = sampled
+            .iter()
+            .map(|frame| frame.get_pixel(0, 0)[0])
+            .collect();
+        assert_eq!(indices, [0, 16, 32, 48]);
+
+        let frames = (0..3).map(|i| Ok(solid_frame(i)));
+        assert_eq!(
+            sample_frames(frames, FrameSampling::Evenly(8))
+                .unwrap()
+                .len(),
+            3
+        );
+
+        let frames = (0..50).map(|i| Ok(solid_frame(i)));
+        assert_eq!(sample_frames(frames, FrameSampling::All).unwrap().len(), 50);
+    }
+
+    #[test]
+    fn test_animated_gif_uses_every_frame() {
+        let mut gif = Vec::new();
+        {
+            let mut encoder = image::codecs::gif::GifEncoder::new(&mut gif);
+            encoder
+                .encode_frames([
+                    Frame::new(RgbaImage::from_pixel(
+                        16,
+                        16,
+                        image::Rgba([220, 30, 30, 255]),
+                    )),
+                    Frame::new(RgbaImage::from_pixel(
+                        16,
+                        16,
+                        image::Rgba([30, 30, 220, 255]),
+                    )),
+                ])
+                .unwrap();
+        }
+
+        let frames = decode_frames(&gif, FrameSampling::default()).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(
+            decode_frames(&gif, FrameSampling::Evenly(1)).unwrap().len(),
+            1
+        );
+
+        let mut haishoku = Haishoku::new();
+        haishoku.load_haishoku_frames(&frames).unwrap();
+        assert_eq!(haishoku.palette.len(), 2);
+        assert!(haishoku.palette.iter().all(|(share, _)| *share == 0.5));
+    }
+
+    #[test]
+    fn test_unsupported_formats_are_named() {
+        let junk = image::load_from_memory(b"not an image").unwrap_err();
+        let error = decode_error(Some(Path::new("wall.heic")), junk);
+        assert_eq!(
+            error.to_string(),
+            "wall.heic: HEIC images are not supported by this build of walrus"
+        );
+        assert!(matches!(
+            decode_image(b"not an image"),
+            Err(WalrusError::UnsupportedFormat { format: None, .. })
+        ));
+
+        // Compiled in formats aren't reported as unsupported
+        let hint = ImageFormatHint::Exact(ImageFormat::Png);
+        let unsupported =
+            ImageError::Unsupported(image::error::UnsupportedError::from_format_and_kind(
+                hint.clone(),
+                UnsupportedErrorKind::Format(hint),
+            ));
+        assert!(matches!(
+            decode_error(None, unsupported),
+            WalrusError::ImageDecode { .. }
+        ));
+    }
+
+    // An AVIF signature followed by garbage
+    const AVIF: &[u8] = b"\0\0\0\x1cftypavif\0\0\0\0avifmif1miaf";
+
+    #[cfg(feature = "avif")]
+    #[test]
+    fn test_avif_is_decoded() {
+        assert!(matches!(
+            decode_image(AVIF),
+            Err(WalrusError::ImageDecode { .. })
+        ));
+    }
+
+    #[cfg(not(feature = "avif"))]
+    #[test]
+    fn test_avif_needs_feature() {
+        match decode_image(AVIF) {
+            Err(WalrusError::UnsupportedFormat { format, .. }) => {
+                assert_eq!(format.as_deref(), Some("AVIF"))
+            }
+            other => panic!("expected an unsupported format, got {:?}", other.err()),
+        }
+    }
+
+    const SVG: &[u8] = br##"<?xml version="1.0"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20">
+  <rect width="40" height="20" fill="#1e90c8"/>
+</svg>"##;
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_svg_is_rasterized() {
+        let image = decode_image(SVG).unwrap();
+        assert_eq!(image.dimensions(), (1024, 512));
+        assert_eq!(image.get_pixel(300, 200), &image::Rgba([30, 144, 200, 255]));
+
+        let frames = decode_frames(SVG, FrameSampling::default()).unwrap();
+        assert_eq!(frames.len(), 1);
+    }
+
+    #[cfg(not(feature = "svg"))]
+    #[test]
+    fn test_svg_needs_feature() {
+        match decode_image(SVG) {
+            Err(WalrusError::UnsupportedFormat { format, .. }) => {
+                assert_eq!(format.as_deref(), Some("SVG"))
+            }
+            other => panic!("expected an unsupported format, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_focus_weighting() {
+        // A red center square covering a quarter of a blue image
+        let image = RgbaImage::from_fn(256, 256, |x, y| {
+            if (64..192).contains(&x) && (64..192).contains(&y) {
+                image::Rgba([220, 30, 30, 255])
+            } else {
+                image::Rgba([30, 30, 220, 255])
+            }
+        });
+        let red_share = |focus| {
+            let mut haishoku = Haishoku::new();
+            haishoku.set_focus(focus);
+            haishoku.load_haishoku(&image).unwrap();
+            haishoku
+                .palette
+                .iter()
+                .find(|(_, color)| color.r > color.b)
+                .unwrap()
+                .0
+        };
+
+        assert_eq!(red_share(Focus::Uniform), 0.25);
+        assert!(red_share(Focus::Center) > 0.33);
+        assert!(red_share(Focus::Edges) < 0.2);
+    }
+
+    #[test]
+    fn test_saliency_boosts_the_subject() {
+        // A small red subject in front of a large flat gray sky
+        let image = RgbaImage::from_fn(256, 256, |x, y| {
+            if (112..144).contains(&x) && (112..176).contains(&y) {
+                image::Rgba([210, 40, 40, 255])
+            } else {
+                image::Rgba([150, 155, 160, 255])
+            }
+        });
+        let subject_share = |saliency| {
+            let mut haishoku = Haishoku::new();
+            haishoku.set_saliency(saliency);
+            haishoku.load_haishoku(&image).unwrap();
+            haishoku
+                .palette
+                .iter()
+                .find(|(_, color)| color.r > 200)
+                .unwrap()
+                .0
+        };
+
+        assert!(subject_share(false) < 0.05);
+        assert!(subject_share(true) > 3.0 * subject_share(false));
+
+        let weights = saliency_weights(&image);
+        assert_eq!(weights[0], 1);
+        assert!(weights[128 * 256 + 128] >= 5);
+    }
+
+    #[test]
+    fn test_regions_and_monitors() {
+        let image = RgbaImage::from_fn(300, 100, |x, _| image::Rgba([x as u8, 0, 0, 255]));
+
+        let cropped = crop_frames(
+            std::slice::from_ref(&image),
+            Region {
+                x: 250,
+                y: 50,
+                width: 100,
+                height: 100,
+            },
+        )
+        .unwrap();
+        assert_eq!(cropped[0].dimensions(), (50, 50));
+        assert_eq!(cropped[0].get_pixel(0, 0)[0], 250);
+
+        let outside = Region {
+            x: 300,
+            y: 0,
+            width: 10,
+            height: 10,
+        };
+        assert!(crop_frames(std::slice::from_ref(&image), outside).is_err());
+
+        let columns = monitor_columns(std::slice::from_ref(&image), 3);
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns[2][0].dimensions(), (100, 100));
+        assert_eq!(columns[2][0].get_pixel(0, 0)[0], 200);
+    }
+
+    #[test]
+    fn test_merged_monitors_count_equally() {
+        let mut small = Haishoku::new();
+        small
+            .load_haishoku(&RgbaImage::from_pixel(
+                10,
+                10,
+                image::Rgba([220, 30, 30, 255]),
+            ))
+            .unwrap();
+        let mut large = Haishoku::new();
+        large
+            .load_haishoku(&RgbaImage::from_fn(400, 100, |x, _| {
+                if x < 300 {
+                    image::Rgba([30, 30, 220, 255])
+                } else {
+                    image::Rgba([30, 220, 30, 255])
+                }
+            }))
+            .unwrap();
+
+        let mut merged = Haishoku::new();
+        merged.load_merged(&[small, large]);
+        assert_eq!(merged.dominant, Some(Color::new(220, 30, 30)));
+        let shares: Vec<f32> = merged.palette.iter().map(|(share, _)| *share).collect();
+        assert_eq!(shares, [0.5, 0.375, 0.125]);
+    }
+
+    #[test]
+    fn test_thin_details_survive_full_resolution() {
+        // One pixel red lines every 8 rows, averaged into pink by the thumbnail
+        let image = RgbaImage::from_fn(1024, 1024, |_, y| {
+            if y % 8 == 0 {
+                image::Rgba([255, 0, 0, 255])
+            } else {
+                image::Rgba([128, 128, 128, 255])
+            }
+        });
+        let red = Color::new(255, 0, 0);
+        let has_red = |haishoku: &Haishoku| haishoku.palette.iter().any(|(_, c)| *c == red);
+
+        let mut haishoku = Haishoku::new();
+        haishoku.load_haishoku(&image).unwrap();
+        assert!(!has_red(&haishoku));
+
+        haishoku.set_resolution(Resolution::Full);
+        haishoku.load_haishoku(&image).unwrap();
+        assert!(has_red(&haishoku));
+        assert_eq!(haishoku.timings().len(), 2);
+
+        // At 64 pixels nearest neighbour samples exactly the red rows, the
+        // blending filters average them away like the box filter
+        haishoku.set_resolution(Resolution::Thumbnail(64));
+        for (filter, keeps_red) in [
+            (ResizeFilter::Nearest, true),
+            (ResizeFilter::Box, false),
+            (ResizeFilter::Triangle, false),
+            (ResizeFilter::Lanczos, false),
+        ] {
+            haishoku.set_filter(filter);
+            haishoku.load_haishoku(&image).unwrap();
+            assert_eq!(has_red(&haishoku), keeps_red, "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn test_resize_filters_differ() {
+        // A vertical edge on a block boundary of the 64 pixel thumbnail
+        let image = RgbaImage::from_fn(1024, 1024, |x, _| {
+            let value = if x < 512 { 64 } else { 192 };
+            image::Rgba([value, value, value, 255])
+        });
+        let row = |filter| {
+            let mut haishoku = Haishoku::new();
+            haishoku.set_filter(filter);
+            haishoku.set_resolution(Resolution::Thumbnail(64));
+            let thumbnail = haishoku.downscale(&image);
+            (0..thumbnail.width())
+                .map(|x| thumbnail.get_pixel(x, 32)[0])
+                .collect::<Vec<u8>>()
+        };
+
+        // Box and nearest keep the edge sharp
+        for filter in [ResizeFilter::Box, ResizeFilter::Nearest] {
+            assert!(row(filter).iter().all(|v| *v == 64 || *v == 192));
+        }
+
+        // Triangle blends across it, Lanczos also rings past both sides
+        let triangle = row(ResizeFilter::Triangle);
+        assert!(triangle.iter().any(|v| *v != 64 && *v != 192));
+        assert!(triangle.iter().all(|v| (64..=192).contains(v)));
+
+        let lanczos = row(ResizeFilter::Lanczos);
+        assert!(lanczos.iter().any(|v| !(64..=192).contains(v)));
+        assert_ne!(lanczos, triangle);
+    }
+
+    #[test]
+    fn test_tiny_images() {
+        let mut haishoku = Haishoku::new();
+
+        let pixel = RgbaImage::from_pixel(1, 1, image::Rgba([12, 34, 56, 255]));
+        haishoku.load_haishoku(&pixel).unwrap();
+        assert_eq!(haishoku.dominant, Some(Color::new(12, 34, 56)));
+
+        haishoku.load_haishoku(&RgbaImage::new(0, 0)).unwrap();
+        assert!(haishoku.palette.is_empty());
+    }
+}
@@ -0,0 +1,33 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::{Result, WalrusError};
+
+/// Run a user hook through sh -c once all outputs are written. The changed
+/// files are passed newline separated in $WALRUS_CHANGED_FILES.
+pub fn run_hook(command: &str, output_dir: &Path, changed_files: &[PathBuf]) -> Result<()> {
+    let changed: Vec<String> = changed_files
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("WALRUS_OUTPUT_DIR", output_dir)
+        .env("WALRUS_CHANGED_FILES", changed.join("\n"))
+        .status()
+        .map_err(|e| WalrusError::Command {
+            command: command.to_string(),
+            message: format!("failed to run hook: {}", e),
+        })?;
+
+    if !status.success() {
+        return Err(WalrusError::Command {
+            command: command.to_string(),
+            message: format!("hook exited with {}", status),
+        });
+    }
+
+    Ok(())
+}
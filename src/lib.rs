@@ -0,0 +1,53 @@
+//! walrus generates pywal-style 16 color schemes from images using the
+//! haishoku algorithm, and renders them into terminal, editor and desktop
+//! configs.
+//!
+//! The `walrus` binary is a thin CLI over this crate. The modules re-exported
+//! at the top level follow semver; the hidden modules only exist to share code
+//! with the binary and may change in any release.
+//!
+//! Images are decoded with the `image` crate's default decoders. The `svg`
+//! feature adds SVG support through resvg, the `avif` feature AVIF support
+//! through dav1d, and the `parallel` feature spreads color extraction over all
+//! cores with rayon.
+//!
+//! ```
+//! use walrus::{Color, TemplateGenerator, TemplateParser};
+//!
+//! let colors: Vec<Color> = (0..16).map(|i| Color::new(i * 16, i * 16, i * 16)).collect();
+//!
+//! let css = TemplateGenerator::new(colors.clone(), false).generate_css_template();
+//! assert!(css.contains("--color15: #f0f0f0;"));
+//!
+//! let parser = TemplateParser::new(colors);
+//! assert_eq!(parser.parse_template("bg={background.strip}"), "bg=000000");
+//! ```
+#![warn(missing_docs)]
+
+pub mod color;
+pub mod error;
+pub mod generator;
+pub mod haishoku;
+pub mod parser;
+pub mod sequences;
+pub mod templates;
+pub mod theme;
+
+// Shared with the walrus binary, not part of the stable API
+#[doc(hidden)]
+pub mod hooks;
+#[doc(hidden)]
+pub mod output;
+#[doc(hidden)]
+pub mod preview;
+#[doc(hidden)]
+pub mod pywalfox;
+#[doc(hidden)]
+pub mod wallpaper;
+
+pub use color::Color;
+pub use error::{Result, WalrusError};
+pub use generator::PywalGenerator;
+pub use parser::TemplateParser;
+pub use sequences::SequenceGenerator;
+pub use templates::TemplateGenerator;
@@ -1,28 +1,41 @@
-use clap::{Arg, Command};
+use clap::{Arg, ArgMatches, Command};
 use std::fs;
-use std::path::PathBuf;
-
-mod color;
-mod generator;
-mod haishoku;
-mod parser;
-mod sequences;
-mod templates;
-
-use generator::PywalGenerator;
-use sequences::SequenceGenerator;
-use templates::TemplateGenerator;
-
-fn initialize_walrus() -> Result<(), Box<dyn std::error::Error>> {
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use walrus::generator::Trace;
+use walrus::haishoku::{Focus, FrameSampling, Region, ResizeFilter, Resolution};
+use walrus::output::OutputWriter;
+use walrus::wallpaper::WallpaperSetter;
+use walrus::{
+    Color, PywalGenerator, Result, SequenceGenerator, TemplateGenerator, TemplateParser,
+    WalrusError, hooks, preview, pywalfox, theme,
+};
+
+// Terminal background alpha, 100 is fully opaque
+const DEFAULT_ALPHA: u8 = 100;
+
+fn initialize_walrus() -> Result<()> {
     let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
     let config_dir = home_dir.join(".config/walrus");
     let templates_dir = config_dir.join("templates");
 
-    fs::create_dir_all(&templates_dir)?;
+    fs::create_dir_all(&templates_dir).map_err(|e| WalrusError::io(&templates_dir, e))?;
 
-    let exe_dir = std::env::current_exe()?
+    let exe = std::env::current_exe().map_err(|e| WalrusError::io("walrus", e))?;
+    let exe_dir = exe
         .parent()
-        .ok_or("Could not determine executable directory")?
+        .ok_or_else(|| {
+            WalrusError::io(
+                &exe,
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "could not determine executable directory",
+                ),
+            )
+        })?
         .to_path_buf();
 
     let possible_template_dirs = vec![
@@ -45,15 +58,16 @@ fn initialize_walrus() -> Result<(), Box<dyn std::error::Error>> {
     )?;
 
     let mut copied_files = Vec::new();
-    for entry in fs::read_dir(&source_dir)? {
-        let entry = entry?;
+    let entries = fs::read_dir(&source_dir).map_err(|e| WalrusError::io(&source_dir, e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| WalrusError::io(&source_dir, e))?;
         let path = entry.path();
 
         if path.is_file() {
             let file_name = path.file_name().unwrap();
             let dest_path = templates_dir.join(file_name);
 
-            fs::copy(&path, &dest_path)?;
+            fs::copy(&path, &dest_path).map_err(|e| WalrusError::io(&dest_path, e))?;
             copied_files.push(file_name.to_string_lossy().to_string());
         }
     }
@@ -79,49 +93,495 @@ fn initialize_walrus() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+// Expand tilde in output directory path
+fn expand_output_dir(output_dir_str: &str) -> PathBuf {
+    if let Some(rest) = output_dir_str.strip_prefix("~/") {
+        let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        home_dir.join(rest)
+    } else if output_dir_str == "~" {
+        dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
+    } else {
+        PathBuf::from(output_dir_str)
+    }
+}
+
+// Image bytes from a file, or from stdin for -
+fn read_image_bytes(image_path: &str) -> Result<Vec<u8>> {
+    let bytes = if image_path == "-" {
+        let mut bytes = Vec::new();
+        io::stdin().read_to_end(&mut bytes).map(|_| bytes)
+    } else {
+        fs::read(image_path)
+    };
+
+    bytes.map_err(|e| WalrusError::io(image_path, e))
+}
+
+// Parse a WIDTHxHEIGHT size such as 1920x1080
+fn parse_size(size: &str) -> Result<(u32, u32)> {
+    size.split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .ok_or_else(|| format!("Invalid size {}, expected WIDTHxHEIGHT", size).into())
+}
+
+// Parse an X,Y,WIDTH,HEIGHT region such as 0,0,2560,1440
+fn parse_region(region: &str) -> Result<Region> {
+    let numbers: Vec<u32> = region
+        .split(',')
+        .map(|number| number.trim().parse())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| format!("Invalid region {}, expected X,Y,WIDTH,HEIGHT", region))?;
+
+    match numbers[..] {
+        [x, y, width, height] if width > 0 && height > 0 => Ok(Region {
+            x,
+            y,
+            width,
+            height,
+        }),
+        _ => Err(format!("Invalid region {}, expected X,Y,WIDTH,HEIGHT", region).into()),
+    }
+}
+
+// Parse a frame count for animated images, or all
+fn parse_frames(frames: &str) -> Result<FrameSampling> {
+    match frames {
+        "all" => Ok(FrameSampling::All),
+        _ => frames
+            .parse()
+            .ok()
+            .filter(|count| *count > 0)
+            .map(FrameSampling::Evenly)
+            .ok_or_else(|| {
+                format!("Invalid frame count {}, expected a number or all", frames).into()
+            }),
+    }
+}
+
+fn parse_saturation(matches: &ArgMatches) -> Result<f32> {
+    let saturation = matches.get_one::<String>("saturation").unwrap();
+    saturation
+        .parse()
+        .map_err(|_| format!("Invalid saturation factor: {}", saturation).into())
+}
+
+// Extract colors from an image file, - for stdin, or raw RGB24 pixels when a size is given
+fn extract_colors(
+    generator: &mut PywalGenerator,
+    image_path: &str,
+    raw_size: Option<&String>,
+    saturation: f32,
+    light: bool,
+) -> Result<Vec<Color>> {
+    if let Some(size) = raw_size {
+        let (width, height) = parse_size(size)?;
+        let pixels = read_image_bytes(image_path)?;
+        return generator.generate_from_rgb(&pixels, width, height, saturation, light);
+    }
+
+    if image_path == "-" {
+        let bytes = read_image_bytes(image_path)?;
+        return generator.generate_from_memory(&bytes, saturation, light);
+    }
+
+    generator.generate_from_image(image_path, saturation, light)
+}
+
+// Stage timings of the last extraction on stderr, nothing for themes
+fn print_timings(timings: &[(&'static str, Duration)]) {
+    if timings.is_empty() {
+        return;
+    }
+
+    for (stage, duration) in timings {
+        eprintln!("{:<10} {:>8.1} ms", stage, duration.as_secs_f64() * 1000.0);
+    }
+    let total: Duration = timings.iter().map(|(_, duration)| *duration).sum();
+    eprintln!("{:<10} {:>8.1} ms", "total", total.as_secs_f64() * 1000.0);
+}
+
+// The palette on stderr, at -vv also every color group and how each color index
+// changed step by step
+fn print_trace(trace: &Trace, verbosity: u8) {
+    if trace.buckets.is_empty() {
+        return;
+    }
+
+    eprintln!(
+        "{} color groups, {} kept in the palette",
+        trace.buckets.len(),
+        trace.palette.len()
+    );
+    if verbosity > 1 {
+        for (weight, color) in &trace.buckets {
+            eprintln!("  group    {} weight {}", color.to_hex(), weight);
+        }
+    }
+    for (fraction, color) in &trace.palette {
+        eprintln!("  palette  {} {:>5.1}%", color.to_hex(), fraction * 100.0);
+    }
+    if verbosity < 2 {
+        return;
+    }
+
+    let sorted: Vec<String> = trace.sorted.iter().map(|color| color.to_hex()).collect();
+    eprintln!("Sorted by YIQ: {}", sorted.join(" "));
+
+    // The first step sets every color, later ones are shown where they change it
+    let Some((first_step, first_colors)) = trace.steps.first() else {
+        return;
+    };
+    for (i, color) in first_colors.iter().enumerate() {
+        let mut history = format!("{} ({})", color.to_hex(), first_step);
+        let mut previous = *color;
+        for (step, colors) in &trace.steps[1..] {
+            if colors[i] != previous {
+                history.push_str(&format!(" -> {} ({})", colors[i].to_hex(), step));
+                previous = colors[i];
+            }
+        }
+        eprintln!("  {:<8} {}", format!("color{}", i), history);
+    }
+}
+
+// Colors for preview: a theme or image if given, otherwise the cached colors.json
+fn load_preview_colors(matches: &ArgMatches) -> Result<(Vec<Color>, Option<PathBuf>)> {
+    if let Some(name) = matches.get_one::<String>("theme") {
+        return Ok((theme::load_theme(name)?, None));
+    }
+
+    if let Some(image_path) = matches.get_one::<String>("image") {
+        let saturation = parse_saturation(matches)?;
+        let light = matches.get_flag("light");
+        let colors = extract_colors(
+            &mut PywalGenerator::new(),
+            image_path,
+            None,
+            saturation,
+            light,
+        )?;
+        // There is no file to thumbnail when the image came from stdin
+        let wallpaper = Some(PathBuf::from(image_path)).filter(|_| image_path != "-");
+        return Ok((colors, wallpaper));
+    }
+
+    let output_dir = expand_output_dir(matches.get_one::<String>("output").unwrap());
+    let colors_path = output_dir.join("colors.json");
+    let content = fs::read_to_string(&colors_path).map_err(|e| {
+        format!(
+            "No cached scheme to preview, run walrus on an image first ({}: {})",
+            colors_path.display(),
+            e
+        )
+    })?;
+
+    // Only the pywal format records the wallpaper
+    let wallpaper = pywalfox::string_field(&content, "wallpaper")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from);
+    Ok((theme::parse_theme(&content)?, wallpaper))
+}
+
+fn run_preview(matches: &ArgMatches) -> Result<()> {
+    let (colors, wallpaper) = load_preview_colors(matches)?;
+
+    if let Some(png_path) = matches.get_one::<String>("png") {
+        preview::write_png(&colors, wallpaper.as_deref(), &PathBuf::from(png_path))?;
+        println!("✓ Wrote palette preview: {}", png_path);
+    } else {
+        print!("{}", preview::terminal_preview(&colors));
+    }
+
+    Ok(())
+}
+
+// Palette swatches followed by the diff of every file that would change
+fn print_dry_run_summary(colors: &[Color], writer: &OutputWriter) {
+    print!("{}", preview::terminal_preview(colors));
+    println!();
+
+    for diff in writer.diffs() {
+        print!("{}", diff);
+    }
+
+    match writer.changed_files().len() {
+        0 => println!("Dry run: no files would change"),
+        count => println!("Dry run: {} file(s) would change", count),
+    }
+}
+
+// How every scheme of a run is written
+struct SchemeSettings<'a> {
+    strip_hash: bool,
+    pywal_json: bool,
+    wallpaper_path: Option<&'a String>,
+    template_dirs: Vec<PathBuf>,
+}
+
+// Write one scheme to output_dir: the built-in formats, the terminal sequences
+// and the first template directory that has templates, which is returned with
+// the files rendered from it
+fn write_scheme(
+    writer: &mut OutputWriter,
+    output_dir: &Path,
+    colors: &[Color],
+    base_scheme: Option<&[Color]>,
+    settings: &SchemeSettings,
+) -> Result<Option<(PathBuf, Vec<PathBuf>)>> {
+    let template_gen = TemplateGenerator::new(colors.to_vec(), settings.strip_hash);
+
+    writer.write(
+        &output_dir.join("colors.sh"),
+        template_gen.generate_shell_template(),
+    )?;
+
+    writer.write(
+        &output_dir.join("colors.css"),
+        template_gen.generate_css_template(),
+    )?;
+
+    let colors_json = if settings.pywal_json {
+        let wallpaper = settings
+            .wallpaper_path
+            .map(|path| {
+                fs::canonicalize(path)
+                    .unwrap_or_else(|_| PathBuf::from(path))
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .unwrap_or_default();
+        template_gen.generate_pywal_json_template(&wallpaper, DEFAULT_ALPHA)
+    } else {
+        template_gen.generate_json_template()
+    };
+    writer.write(&output_dir.join("colors.json"), colors_json)?;
+
+    writer.write(
+        &output_dir.join("colors.Xresources"),
+        template_gen.generate_xresources_template(),
+    )?;
+
+    writer.write(
+        &output_dir.join("colors.scss"),
+        template_gen
+            .generate_scss_template(settings.wallpaper_path.map_or("", |path| path.as_str())),
+    )?;
+
+    for (file_name, content) in &template_gen.generate_builtin_templates() {
+        writer.write(&output_dir.join(file_name), content)?;
+    }
+
+    if let Some(scheme) = base_scheme {
+        let file_name = if scheme.len() > 16 {
+            "colors-base24.yaml"
+        } else {
+            "colors-base16.yaml"
+        };
+        writer.write(
+            &output_dir.join(file_name),
+            template_gen.generate_base16_template(scheme),
+        )?;
+    }
+
+    let sequences =
+        SequenceGenerator::new(colors.to_vec(), DEFAULT_ALPHA).generate_sequences(false);
+    writer.write(&output_dir.join("sequences"), &sequences)?;
+
+    for template_dir in &settings.template_dirs {
+        if template_dir.exists() && template_dir.is_dir() {
+            let mut template_parser = TemplateParser::new(colors.to_vec());
+            if let Some(scheme) = base_scheme {
+                template_parser.set_base_scheme(scheme);
+            }
+            let processed_files =
+                template_parser.process_template_directory(template_dir, output_dir, writer)?;
+
+            if !processed_files.is_empty() {
+                return Ok(Some((template_dir.clone(), processed_files)));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+// Arguments shared by the top level command and the gen subcommand
+fn generation_args() -> Vec<Arg> {
+    vec![
+        Arg::new("image")
+            .help("The image file to process, - reads it from stdin")
+            .required(false)
+            .index(1),
+        Arg::new("strip")
+            .long("strip")
+            .help("Strip # from hex colors")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("templates")
+            .short('t')
+            .long("templates")
+            .help("Directory containing template files to process")
+            .value_name("TEMPLATES_DIR"),
+        Arg::new("wallpaper")
+            .short('w')
+            .long("wallpaper")
+            .help("Set the image as wallpaper, detecting the setter from the session")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("setter")
+            .long("setter")
+            .help(
+                "Wallpaper setter to use (swww, swaybg, hyprpaper, feh, xwallpaper, gsettings)",
+            )
+            .value_name("SETTER"),
+        Arg::new("wallpaper_cmd")
+            .long("wallpaper-cmd")
+            .help(
+                "Custom command to set the wallpaper, {image} is replaced with the image path",
+            )
+            .value_name("COMMAND"),
+        Arg::new("json_format")
+            .long("json-format")
+            .help("Format of colors.json: thorn (stripped hex) or pywal (pywal schema)")
+            .value_parser(["thorn", "pywal"])
+            .default_value("thorn"),
+        Arg::new("base16")
+            .long("base16")
+            .help("Derive a Base16 scheme, exposed as {base00}-{base0F} and colors-base16.yaml")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("base24")
+            .long("base24")
+            .help("Derive a Base24 scheme, exposed as {base00}-{base17} and colors-base24.yaml")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("hook")
+            .long("hook")
+            .help("Command to run after generation, changed files are listed in $WALRUS_CHANGED_FILES (repeatable)")
+            .value_name("COMMAND")
+            .action(clap::ArgAction::Append),
+        Arg::new("dry_run")
+            .long("dry-run")
+            .help("Show a diff of what would change without writing files, sending sequences, or running hooks")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("raw_size")
+            .long("raw-size")
+            .help("Read the image as raw RGB24 pixels of the given size, e.g. 1920x1080")
+            .value_name("WIDTHxHEIGHT"),
+        Arg::new("region")
+            .long("region")
+            .help("Only extract colors from this part of the image, in pixels")
+            .value_name("X,Y,WIDTH,HEIGHT"),
+        Arg::new("focus")
+            .long("focus")
+            .help("Let the center or the edges of the image count more towards the palette")
+            .value_parser(["uniform", "center", "edges"])
+            .default_value("uniform"),
+        Arg::new("saliency")
+            .long("saliency")
+            .help("Let colorful, high contrast and central pixels count more, so subjects aren't drowned out by sky or walls")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("monitors")
+            .long("monitors")
+            .help("The image spans this many monitors side by side, each counts the same")
+            .value_name("COUNT")
+            .value_parser(clap::value_parser!(u32).range(1..)),
+        Arg::new("per_monitor")
+            .long("per-monitor")
+            .help("Also write a scheme per monitor to monitor-1, monitor-2, ... in the output directory")
+            .requires("monitors")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("frames")
+            .long("frames")
+            .help("Frames of an animated GIF, APNG or WebP to sample, spread evenly, or all (default 8)")
+            .value_name("COUNT"),
+        Arg::new("thumbnail_size")
+            .long("thumbnail-size")
+            .help("Count colors on a square thumbnail with sides of this many pixels")
+            .value_name("PIXELS")
+            .value_parser(clap::value_parser!(u32).range(1..))
+            .default_value("256"),
+        Arg::new("filter")
+            .long("filter")
+            .help("Filter to scale the image down with, box averages, nearest keeps thin details")
+            .value_parser(["box", "nearest", "triangle", "lanczos"])
+            .default_value("box"),
+        Arg::new("full_resolution")
+            .long("full-resolution")
+            .help("Count the colors of every pixel instead of a thumbnail, slower but most accurate")
+            .conflicts_with_all(["thumbnail_size", "filter"])
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("verbose")
+            .short('v')
+            .long("verbose")
+            .help("Report timings and the extracted palette, -vv also every color group and adjustment step")
+            .action(clap::ArgAction::Count),
+        Arg::new("debug_json")
+            .long("debug-json")
+            .help("Write the color groups, palette, YIQ order and every adjustment step of each color to this JSON file")
+            .value_name("FILE")
+            .conflicts_with("theme"),
+    ]
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+fn run() -> Result<ExitCode> {
     let matches = Command::new("pywal-haishoku")
         .about("A minimal pywal-style color generator using haishoku algorithm")
         .version("1.0")
-        .arg(
-            Arg::new("image")
-                .help("The image file to process")
-                .required(false)
-                .index(1),
-        )
         .arg(
             Arg::new("output")
                 .short('o')
                 .long("output")
                 .help("Output directory for template files")
-                .default_value("~/.cache/walrus"),
+                .default_value("~/.cache/walrus")
+                .global(true),
         )
         .arg(
             Arg::new("saturation")
                 .short('s')
                 .long("saturation")
                 .help("Saturation factor (1.0 = normal, 0.2 = 20% saturation)")
-                .default_value("1.0"),
-        )
-        .arg(
-            Arg::new("strip")
-                .long("strip")
-                .help("Strip # from hex colors")
-                .action(clap::ArgAction::SetTrue),
+                .default_value("1.0")
+                .global(true),
         )
         .arg(
             Arg::new("light")
                 .short('l')
                 .long("light")
                 .help("Generate a light colorscheme")
+                .action(clap::ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("theme")
+                .long("theme")
+                .help("Use a predefined theme (bundled name, ~/.config/walrus/themes name, or file path)")
+                .value_name("THEME")
+                .global(true),
+        )
+        .arg(
+            Arg::new("list_themes")
+                .long("list-themes")
+                .help("List the bundled themes")
                 .action(clap::ArgAction::SetTrue),
         )
         .arg(
-            Arg::new("templates")
-                .short('t')
-                .long("templates")
-                .help("Directory containing template files to process")
-                .value_name("TEMPLATES_DIR"),
+            Arg::new("pywalfox_host")
+                .long("pywalfox-host")
+                .help("Run as the pywalfox native messaging host, serving colors.json from the output directory")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("pywalfox_install")
+                .long("pywalfox-install")
+                .help("Register walrus as the pywalfox native messaging host for Firefox")
+                .action(clap::ArgAction::SetTrue),
         )
         .arg(
             Arg::new("init")
@@ -130,74 +590,165 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .help("Initialize walrus by creating ~/.config/walrus and copying templates")
                 .action(clap::ArgAction::SetTrue),
         )
+        .args(generation_args())
+        .subcommand(
+            Command::new("gen")
+                .about("Generate colors and templates from an image or theme, - reads the image from stdin")
+                .args(generation_args()),
+        )
+        .subcommand(
+            Command::new("preview")
+                .about("Preview the palette in the terminal, from an image, a theme, or the last generated scheme")
+                .arg(
+                    Arg::new("image")
+                        .help("Image to extract colors from instead of the cached scheme")
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("png")
+                        .long("png")
+                        .help("Write a PNG swatch sheet with the wallpaper thumbnail instead")
+                        .value_name("FILE"),
+                ),
+        )
         .get_matches();
 
+    if let Some(preview_matches) = matches.subcommand_matches("preview") {
+        return run_preview(preview_matches).map(|()| ExitCode::SUCCESS);
+    }
+
     if matches.get_flag("init") {
-        return initialize_walrus();
+        return initialize_walrus().map(|()| ExitCode::SUCCESS);
     }
 
-    let image_path = matches
-        .get_one::<String>("image")
-        .ok_or("Image path is required. Use --help for usage information.")?;
+    if matches.get_flag("list_themes") {
+        for name in theme::bundled_theme_names() {
+            println!("{}", name);
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if matches.get_flag("pywalfox_host") {
+        let output_dir = expand_output_dir(matches.get_one::<String>("output").unwrap());
+        return pywalfox::run_native_host(&output_dir).map(|()| ExitCode::SUCCESS);
+    }
+
+    if matches.get_flag("pywalfox_install") {
+        let output_dir = expand_output_dir(matches.get_one::<String>("output").unwrap());
+        let manifest_path = pywalfox::install_native_host(&output_dir)?;
+        println!(
+            "✓ Installed pywalfox native host manifest: {}",
+            manifest_path.display()
+        );
+        println!("Run walrus with --json-format pywal so pywalfox gets the wallpaper path too.");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // walrus gen takes the same arguments as plain walrus
+    let matches = matches.subcommand_matches("gen").unwrap_or(&matches);
+
+    let theme_name = matches.get_one::<String>("theme");
+    let image_path = matches.get_one::<String>("image");
+    let raw_size = matches.get_one::<String>("raw_size");
+    // Images from stdin or raw pixels have no file to use as the wallpaper
+    let wallpaper_path = image_path.filter(|path| path.as_str() != "-" && raw_size.is_none());
+    if image_path.is_none() && theme_name.is_none() {
+        return Err("Image path is required. Use --help for usage information.".into());
+    }
     let output_dir_str = matches.get_one::<String>("output").unwrap();
-    let saturation: f32 = matches.get_one::<String>("saturation").unwrap().parse()?;
+    let saturation = parse_saturation(matches)?;
+    let region = matches
+        .get_one::<String>("region")
+        .map(|region| parse_region(region))
+        .transpose()?;
+    let frame_sampling = matches
+        .get_one::<String>("frames")
+        .map(|frames| parse_frames(frames))
+        .transpose()?;
     let strip_hash = matches.get_flag("strip");
     let light = matches.get_flag("light");
-
-    // Expand tilde in output directory path
-    let output_dir = if output_dir_str.starts_with("~/") {
-        let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        home_dir.join(&output_dir_str[2..])
-    } else if output_dir_str == "~" {
-        dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
+    let dry_run = matches.get_flag("dry_run");
+
+    // --setter and --wallpaper-cmd imply --wallpaper
+    let wallpaper_setter = if let Some(command) = matches.get_one::<String>("wallpaper_cmd") {
+        Some(WallpaperSetter::Custom(command.clone()))
+    } else if let Some(name) = matches.get_one::<String>("setter") {
+        Some(
+            WallpaperSetter::from_name(name)
+                .ok_or(format!("Unknown wallpaper setter: {}", name))?,
+        )
     } else {
-        PathBuf::from(output_dir_str)
+        None
     };
 
-    fs::create_dir_all(&output_dir)?;
-
-    let mut generator = PywalGenerator::new();
-    let colors = generator.generate_from_image(image_path, saturation, light)?;
-
-    let template_gen = TemplateGenerator::new(colors.clone(), strip_hash);
-
-    // Generate terminal sequences
-    let sequence_gen = SequenceGenerator::new(colors.clone(), 100); // 100 is the default alpha value
-
-    fs::write(
-        output_dir.join("colors.sh"),
-        template_gen.generate_shell_template(),
-    )?;
-
-    fs::write(
-        output_dir.join("colors.css"),
-        template_gen.generate_css_template(),
-    )?;
-
-    fs::write(
-        output_dir.join("colors.json"),
-        template_gen.generate_json_template(),
-    )?;
+    let output_dir = expand_output_dir(output_dir_str);
 
-    fs::write(
-        output_dir.join("colors.Xresources"),
-        template_gen.generate_xresources_template(),
-    )?;
+    if !dry_run {
+        fs::create_dir_all(&output_dir).map_err(|e| WalrusError::io(&output_dir, e))?;
+    }
 
-    fs::write(
-        output_dir.join("colors.scss"),
-        template_gen.generate_scss_template(image_path),
-    )?;
+    let mut generator = PywalGenerator::new();
+    if let Some(sampling) = frame_sampling {
+        generator.set_frame_sampling(sampling);
+    }
+    if let Some(region) = region {
+        generator.set_region(region);
+    }
+    generator.set_focus(
+        match matches.get_one::<String>("focus").map(String::as_str) {
+            Some("center") => Focus::Center,
+            Some("edges") => Focus::Edges,
+            _ => Focus::Uniform,
+        },
+    );
+    generator.set_saliency(matches.get_flag("saliency"));
+    generator.set_filter(
+        match matches.get_one::<String>("filter").map(String::as_str) {
+            Some("nearest") => ResizeFilter::Nearest,
+            Some("triangle") => ResizeFilter::Triangle,
+            Some("lanczos") => ResizeFilter::Lanczos,
+            _ => ResizeFilter::Box,
+        },
+    );
+    generator.set_resolution(if matches.get_flag("full_resolution") {
+        Resolution::Full
+    } else {
+        Resolution::Thumbnail(*matches.get_one::<u32>("thumbnail_size").unwrap())
+    });
+    if let Some(monitors) = matches.get_one::<u32>("monitors") {
+        generator.set_monitors(*monitors);
+    }
+    let colors = match (theme_name, image_path) {
+        (Some(name), _) => theme::load_theme(name)?,
+        (None, Some(image_path)) => {
+            extract_colors(&mut generator, image_path, raw_size, saturation, light)?
+        }
+        (None, None) => unreachable!(),
+    };
 
-    let sequences = sequence_gen.generate_sequences(false);
-    fs::write(output_dir.join("sequences"), &sequences)?;
+    let verbosity = matches.get_count("verbose");
+    if verbosity > 0 {
+        print_timings(generator.timings());
+        print_trace(generator.trace(), verbosity);
+    }
 
-    // Send sequences to all open terminals
-    if let Err(e) = sequence_gen.send_sequences_to_terminals(false) {
-        eprintln!("Warning: Failed to send sequences to terminals: {}", e);
+    let base24 = matches.get_flag("base24");
+    let base_scheme = if base24 || matches.get_flag("base16") {
+        // A base16 theme already has its roles, only the palette is derived
+        let theme_scheme = match theme_name {
+            Some(name) => theme::load_base_scheme(name)?,
+            None => None,
+        };
+        Some(match (theme_scheme, base24) {
+            (Some(scheme), true) if scheme.len() == 24 => scheme,
+            (Some(scheme), true) => generator.extend_base24(scheme)?,
+            (Some(scheme), false) => scheme[..16].to_vec(),
+            (None, true) => generator.generate_base24(&colors)?,
+            (None, false) => generator.generate_base16(&colors)?,
+        })
     } else {
-        println!("Applied colors to open terminals");
-    }
+        None
+    };
 
     // Process template files
     // Check for template directory in the following order:
@@ -216,36 +767,122 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     template_dirs.push(PathBuf::from("templates"));
     template_dirs.push(PathBuf::from("/usr/share/walrus/templates"));
 
-    let mut processed_any = false;
-    for template_dir in template_dirs {
-        if template_dir.exists() && template_dir.is_dir() {
-            let template_parser = parser::TemplateParser::new(colors.clone());
-            let processed_files =
-                template_parser.process_template_directory(&template_dir, &output_dir)?;
+    let settings = SchemeSettings {
+        strip_hash,
+        pywal_json: matches.get_one::<String>("json_format").map(String::as_str) == Some("pywal"),
+        wallpaper_path,
+        template_dirs,
+    };
 
-            if !processed_files.is_empty() {
-                println!("Processed template files from {}:", template_dir.display());
-                for file in processed_files {
-                    println!("  - {}", file.file_name().unwrap().to_string_lossy());
-                }
-                processed_any = true;
-                break;
+    // Outputs are written atomically and skipped when unchanged
+    let mut writer = if dry_run {
+        OutputWriter::dry_run()
+    } else {
+        OutputWriter::new()
+    };
+
+    // Goes through the writer too, so a dry run only shows its diff
+    if let Some(path) = matches.get_one::<String>("debug_json") {
+        writer.write(Path::new(path), generator.trace().to_json())?;
+    }
+
+    let processed_templates = write_scheme(
+        &mut writer,
+        &output_dir,
+        &colors,
+        base_scheme.as_deref(),
+        &settings,
+    )?;
+
+    // A scheme per monitor of a spanned wallpaper, in monitor-1, monitor-2, ...
+    if matches.get_flag("per_monitor") {
+        for (i, monitor_colors) in generator.monitor_schemes().iter().enumerate() {
+            let monitor_base = match base_scheme.as_ref().map(Vec::len) {
+                Some(24) => Some(generator.generate_base24(monitor_colors)?),
+                Some(_) => Some(generator.generate_base16(monitor_colors)?),
+                None => None,
+            };
+            write_scheme(
+                &mut writer,
+                &output_dir.join(format!("monitor-{}", i + 1)),
+                monitor_colors,
+                monitor_base.as_deref(),
+                &settings,
+            )?;
+        }
+    }
+
+    // Failures past this point are warnings, the first one still sets the exit code
+    let mut warning_exit_code = None;
+
+    // Send sequences to all open terminals, a dry run leaves them alone
+    if !dry_run {
+        let sequence_gen = SequenceGenerator::new(colors.clone(), DEFAULT_ALPHA);
+        match sequence_gen.send_sequences_to_terminals(false) {
+            Ok(()) => println!("Applied colors to open terminals"),
+            Err(e) => {
+                eprintln!("Warning: {}", e);
+                warning_exit_code.get_or_insert(e.exit_code());
             }
         }
     }
 
-    if !processed_any {
-        println!("No template files found. Templates can be placed in:");
-        println!("  - ~/.config/walrus/templates (user templates)");
-        println!("  - ./templates (development)");
-        println!("  - /usr/share/walrus/templates (system-wide)");
-        println!("  - Or specify with --templates <dir>");
+    match processed_templates {
+        Some((template_dir, processed_files)) => {
+            println!("Processed template files from {}:", template_dir.display());
+            for file in processed_files {
+                println!("  - {}", file.file_name().unwrap().to_string_lossy());
+            }
+        }
+        None => {
+            println!("No template files found. Templates can be placed in:");
+            println!("  - ~/.config/walrus/templates (user templates)");
+            println!("  - ./templates (development)");
+            println!("  - /usr/share/walrus/templates (system-wide)");
+            println!("  - Or specify with --templates <dir>");
+        }
     }
 
-    println!(
-        "Colors extracted using haishoku algorithm and templates generated in: {}",
-        output_dir.display()
-    );
+    if dry_run {
+        print_dry_run_summary(&colors, &writer);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let wallpaper_setter = match wallpaper_setter {
+        Some(setter) => Some(setter),
+        None if matches.get_flag("wallpaper") => WallpaperSetter::detect(),
+        None => None,
+    };
+
+    match (wallpaper_setter, wallpaper_path) {
+        (Some(setter), Some(wallpaper_path)) => match setter.set_wallpaper(wallpaper_path) {
+            Ok(()) => println!("Set wallpaper using {}", setter.name()),
+            Err(e) => {
+                eprintln!("Warning: Failed to set wallpaper: {}", e);
+                warning_exit_code.get_or_insert(e.exit_code());
+            }
+        },
+        (Some(setter), None) => eprintln!(
+            "Warning: No image file given, not setting wallpaper with {}",
+            setter.name()
+        ),
+        (None, _) if matches.get_flag("wallpaper") => eprintln!(
+            "Warning: No supported wallpaper setter found, use --setter or --wallpaper-cmd"
+        ),
+        (None, _) => {}
+    }
+
+    match theme_name {
+        Some(name) => println!(
+            "Colors loaded from theme {} and templates generated in: {}",
+            name,
+            output_dir.display()
+        ),
+        None => println!(
+            "Colors extracted using haishoku algorithm and templates generated in: {}",
+            output_dir.display()
+        ),
+    }
     println!("Saturation factor: {}", saturation);
     println!("Strip hash: {}", strip_hash);
     println!("Generated files:");
@@ -254,7 +891,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  - colors.json (JSON format)");
     println!("  - colors.Xresources (X11 resources)");
     println!("  - colors.scss (SCSS variables)");
+    for (file_name, _) in
+        TemplateGenerator::new(colors.clone(), strip_hash).generate_builtin_templates()
+    {
+        println!("  - {}", file_name);
+    }
+    if let Some(scheme) = &base_scheme {
+        println!(
+            "  - colors-base{}.yaml (Base{} scheme)",
+            scheme.len(),
+            scheme.len()
+        );
+    }
     println!("  - sequences (terminal escape sequences)");
+    println!("{} file(s) changed", writer.changed_files().len());
 
-    Ok(())
+    if let Some(commands) = matches.get_many::<String>("hook") {
+        for command in commands {
+            if let Err(e) = hooks::run_hook(command, &output_dir, writer.changed_files()) {
+                eprintln!("Warning: {}", e);
+                warning_exit_code.get_or_insert(e.exit_code());
+            }
+        }
+    }
+
+    Ok(warning_exit_code.map_or(ExitCode::SUCCESS, ExitCode::from))
 }
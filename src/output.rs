@@ -0,0 +1,229 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use similar::TextDiff;
+
+use crate::error::{Result, WalrusError};
+
+/// Writes outputs atomically and keeps track of which files actually changed
+pub struct OutputWriter {
+    changed: Vec<PathBuf>,
+    dry_run: bool,
+    diffs: Vec<String>,
+}
+
+impl Default for OutputWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutputWriter {
+    pub fn new() -> Self {
+        OutputWriter {
+            changed: Vec::new(),
+            dry_run: false,
+            diffs: Vec::new(),
+        }
+    }
+
+    /// Leave the filesystem alone and collect a unified diff per changed file
+    pub fn dry_run() -> Self {
+        OutputWriter {
+            dry_run: true,
+            ..Self::new()
+        }
+    }
+
+    /// Write through a temp file in the same directory and rename it over the
+    /// target, so readers never see a half-written file. Identical content is
+    /// left untouched. Returns whether the file changed.
+    pub fn write(&mut self, path: &Path, contents: impl AsRef<[u8]>) -> Result<bool> {
+        self.write_file(path, contents.as_ref())
+            .map_err(|e| WalrusError::io(path, e))
+    }
+
+    fn write_file(&mut self, path: &Path, contents: &[u8]) -> io::Result<bool> {
+        let existing = fs::read(path).ok();
+        if existing.as_deref() == Some(contents) {
+            return Ok(false);
+        }
+
+        if self.dry_run {
+            self.diffs
+                .push(unified_diff(path, existing.as_deref(), contents));
+            self.changed.push(path.to_path_buf());
+            return Ok(true);
+        }
+
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Replace the file a symlink points to rather than the symlink, and
+        // keep the mode of the file being replaced
+        let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let permissions = fs::metadata(&target).ok().map(|meta| meta.permissions());
+
+        let file_name = target
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "missing file name"))?;
+        let temp_path = target.with_file_name(format!(
+            ".{}.walrus-{}",
+            file_name.to_string_lossy(),
+            std::process::id()
+        ));
+
+        let result = File::create(&temp_path)
+            .and_then(|mut file| {
+                file.write_all(contents)?;
+                if let Some(permissions) = permissions {
+                    file.set_permissions(permissions)?;
+                }
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&temp_path, &target));
+
+        if let Err(e) = result {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e);
+        }
+
+        self.changed.push(path.to_path_buf());
+        Ok(true)
+    }
+
+    pub fn changed_files(&self) -> &[PathBuf] {
+        &self.changed
+    }
+
+    pub fn diffs(&self) -> &[String] {
+        &self.diffs
+    }
+}
+
+fn unified_diff(path: &Path, old: Option<&[u8]>, new: &[u8]) -> String {
+    let old_text = old.map(String::from_utf8_lossy).unwrap_or_default();
+    let new_text = String::from_utf8_lossy(new);
+    let path = path.display().to_string();
+    let old_header = if old.is_some() { &path } else { "/dev/null" };
+
+    let diff = TextDiff::from_lines(old_text.as_ref(), new_text.as_ref())
+        .unified_diff()
+        .header(old_header, &path)
+        .to_string();
+    visible_controls(&diff)
+}
+
+// Control characters in caret notation (ESC as ^[), so printing a diff of
+// the terminal sequences doesn't recolor the terminal showing it
+fn visible_controls(text: &str) -> String {
+    let mut visible = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' | '\t' => visible.push(c),
+            '\u{0}'..='\u{1f}' => {
+                visible.push('^');
+                visible.push((c as u8 + 0x40) as char);
+            }
+            '\u{7f}' => visible.push_str("^?"),
+            '\u{80}'..='\u{9f}' => visible.push_str(&format!("<U+{:04X}>", c as u32)),
+            c => visible.push(c),
+        }
+    }
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_skips_identical_content() {
+        let dir = std::env::temp_dir().join(format!("walrus-output-{}", std::process::id()));
+        let path = dir.join("nested/colors.css");
+
+        let mut writer = OutputWriter::new();
+        assert!(writer.write(&path, "a").unwrap());
+        assert!(!writer.write(&path, "a").unwrap());
+        assert!(writer.write(&path, "b").unwrap());
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "b");
+        assert_eq!(writer.changed_files(), &[path.clone(), path.clone()]);
+
+        // No temp files left behind
+        assert_eq!(fs::read_dir(dir.join("nested")).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_keeps_mode_and_symlinks() {
+        use std::os::unix::fs::{PermissionsExt, symlink};
+
+        let dir = std::env::temp_dir().join(format!("walrus-symlink-{}", std::process::id()));
+        fs::create_dir_all(dir.join("dotfiles")).unwrap();
+        let real = dir.join("dotfiles/colors.sh");
+        let link = dir.join("colors.sh");
+        fs::write(&real, "old").unwrap();
+        fs::set_permissions(&real, fs::Permissions::from_mode(0o750)).unwrap();
+        symlink(&real, &link).unwrap();
+
+        let mut writer = OutputWriter::new();
+        assert!(writer.write(&link, "new").unwrap());
+
+        assert!(
+            fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(fs::read_to_string(&real).unwrap(), "new");
+        let mode = fs::metadata(&real).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dry_run_only_diffs() {
+        let dir = std::env::temp_dir().join(format!("walrus-dry-run-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let existing = dir.join("colors.css");
+        fs::write(&existing, "color0: #000000;\ncolor1: #111111;\n").unwrap();
+
+        let mut writer = OutputWriter::dry_run();
+        assert!(
+            writer
+                .write(&existing, "color0: #000000;\ncolor1: #222222;\n")
+                .unwrap()
+        );
+        assert!(writer.write(&dir.join("new.css"), "a\n").unwrap());
+
+        assert_eq!(
+            fs::read_to_string(&existing).unwrap(),
+            "color0: #000000;\ncolor1: #111111;\n"
+        );
+        assert!(!dir.join("new.css").exists());
+
+        assert!(writer.diffs()[0].contains("-color1: #111111;\n+color1: #222222;\n"));
+        assert!(writer.diffs()[1].starts_with("--- /dev/null\n"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dry_run_diff_escapes_sequences() {
+        let path = std::env::temp_dir().join(format!("walrus-sequences-{}", std::process::id()));
+
+        let mut writer = OutputWriter::dry_run();
+        writer
+            .write(&path, "\x1b]4;0;#000000\x1b\\\x1b]11;#000000\x07\u{9b}0m\n")
+            .unwrap();
+
+        let diff = &writer.diffs()[0];
+        assert!(diff.contains("+^[]4;0;#000000^[\\^[]11;#000000^G<U+009B>0m\n"));
+        assert!(!diff.chars().any(|c| c.is_control() && c != '\n'));
+    }
+}
@@ -1,16 +1,22 @@
+//! User templates with {color0}-style variables
+
 use regex::Regex;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::color::Color;
+use crate::error::{Result, WalrusError};
+use crate::output::OutputWriter;
 
+/// Fills {color0}, {background.strip} and friends in user templates
 pub struct TemplateParser {
     colors: Vec<Color>,
     variables: HashMap<String, String>,
 }
 
 impl TemplateParser {
+    /// Parser exposing color0-15, background, foreground and cursor
     pub fn new(colors: Vec<Color>) -> Self {
         let mut parser = TemplateParser {
             colors,
@@ -54,6 +60,17 @@ impl TemplateParser {
             .insert("cursor.strip".to_string(), foreground.to_hex_stripped());
     }
 
+    /// Expose a Base16/Base24 scheme as {base00}-{base0F} (and {base10}-{base17})
+    pub fn set_base_scheme(&mut self, scheme: &[Color]) {
+        for (i, color) in scheme.iter().enumerate() {
+            self.variables
+                .insert(format!("base{:02X}", i), color.to_hex());
+            self.variables
+                .insert(format!("base{:02X}.strip", i), color.to_hex_stripped());
+        }
+    }
+
+    /// Replace known {variables}, unknown ones are left as they are
     pub fn parse_template(&self, template_content: &str) -> String {
         let re = Regex::new(r"\{([a-zA-Z0-9._]+)\}").unwrap();
 
@@ -68,44 +85,51 @@ impl TemplateParser {
         result.to_string()
     }
 
+    /// Render one template file to output_path
     pub fn process_template_file(
         &self,
         template_path: &Path,
         output_path: &Path,
-    ) -> std::io::Result<()> {
+        writer: &mut OutputWriter,
+    ) -> Result<()> {
         // Read the template file
-        let template_content = fs::read_to_string(template_path)?;
+        let template_bytes =
+            fs::read(template_path).map_err(|e| WalrusError::io(template_path, e))?;
+        let template_content =
+            String::from_utf8(template_bytes).map_err(|e| WalrusError::TemplateParse {
+                path: template_path.to_path_buf(),
+                message: format!("not valid UTF-8 ({})", e.utf8_error()),
+            })?;
 
         let filled_content = self.parse_template(&template_content);
 
         // Write the filled template to the output path
-        fs::write(output_path, filled_content)?;
+        writer.write(output_path, filled_content)?;
 
         Ok(())
     }
 
+    /// Render every file in template_dir into output_dir under the same name,
+    /// returns the rendered paths
     pub fn process_template_directory(
         &self,
         template_dir: &Path,
         output_dir: &Path,
-    ) -> std::io::Result<Vec<PathBuf>> {
+        writer: &mut OutputWriter,
+    ) -> Result<Vec<PathBuf>> {
         let mut processed_files = Vec::new();
 
-        // Create output directory if it doesn't exist
-        if !output_dir.exists() {
-            fs::create_dir_all(output_dir)?;
-        }
-
         // Process each file in the template directory
-        for entry in fs::read_dir(template_dir)? {
-            let entry = entry?;
+        let entries = fs::read_dir(template_dir).map_err(|e| WalrusError::io(template_dir, e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| WalrusError::io(template_dir, e))?;
             let path = entry.path();
 
             if path.is_file() {
                 let file_name = path.file_name().unwrap().to_string_lossy().to_string();
                 let output_path = output_dir.join(&file_name);
 
-                self.process_template_file(&path, &output_path)?;
+                self.process_template_file(&path, &output_path, writer)?;
                 processed_files.push(output_path);
             }
         }
@@ -114,3 +138,27 @@ impl TemplateParser {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_template_reports_path() {
+        let dir = std::env::temp_dir().join(format!("walrus-parser-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let template_path = dir.join("colors.conf");
+        fs::write(&template_path, b"\xff{color0}").unwrap();
+
+        let parser = TemplateParser::new(vec![Color::new(1, 2, 3); 16]);
+        let mut writer = OutputWriter::dry_run();
+        let error = parser
+            .process_template_file(&template_path, &dir.join("out"), &mut writer)
+            .unwrap_err();
+
+        assert!(
+            matches!(&error, WalrusError::TemplateParse { path, .. } if *path == template_path)
+        );
+        assert_eq!(error.exit_code(), 5);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,293 @@
+use image::{Rgb, RgbImage, RgbaImage, imageops};
+use std::io;
+use std::path::Path;
+
+use crate::color::Color;
+use crate::error::{Result, WalrusError};
+use crate::haishoku;
+use crate::templates::TemplateGenerator;
+
+mod font;
+
+const SAMPLE_TEXT: &str = "The quick brown fox jumps over the lazy dog";
+const RESET: &str = "\x1b[0m";
+
+// Swatch sheet layout, in pixels
+const SHEET_PADDING: u32 = 24;
+const SWATCH_WIDTH: u32 = 136;
+const SWATCH_HEIGHT: u32 = 72;
+const SWATCH_GAP: u32 = 8;
+const SWATCHES_PER_ROW: u32 = 8;
+const SECTION_GAP: u32 = 24;
+const TEXT_SCALE: u32 = 2;
+// Two rows for color0-15 and one for the roles
+const GRID_WIDTH: u32 = SWATCHES_PER_ROW * SWATCH_WIDTH + (SWATCHES_PER_ROW - 1) * SWATCH_GAP;
+const GRID_HEIGHT: u32 = 3 * SWATCH_HEIGHT + SWATCH_GAP + SECTION_GAP;
+
+/// color0-15 and the semantic roles as truecolor swatches, with the hex value,
+/// the contrast ratio against the background and a line of sample text
+pub fn terminal_preview(colors: &[Color]) -> String {
+    let background = colors[0];
+
+    let mut output = String::new();
+    output.push_str("Colors\n");
+    for (i, color) in colors.iter().enumerate() {
+        output.push_str(&preview_line(&format!("color{}", i), *color, background));
+    }
+
+    output.push_str("\nRoles\n");
+    for (name, color) in TemplateGenerator::new(colors.to_vec(), false).roles() {
+        output.push_str(&preview_line(name, color, background));
+    }
+
+    output
+}
+
+fn preview_line(name: &str, color: Color, background: Color) -> String {
+    let ratio = color.contrast_ratio(background);
+
+    format!(
+        "  {}      {} {:<10} {} {:>5.2}:1 {:<3}  {}{} {} {}\n",
+        background_escape(color),
+        RESET,
+        name,
+        color.to_hex(),
+        ratio,
+        wcag_level(ratio),
+        background_escape(background),
+        foreground_escape(color),
+        SAMPLE_TEXT,
+        RESET
+    )
+}
+
+// WCAG 2 conformance for normal sized text
+fn wcag_level(ratio: f32) -> &'static str {
+    if ratio >= 7.0 {
+        "AAA"
+    } else if ratio >= 4.5 {
+        "AA"
+    } else {
+        ""
+    }
+}
+
+fn background_escape(color: Color) -> String {
+    format!("\x1b[48;2;{};{};{}m", color.r, color.g, color.b)
+}
+
+fn foreground_escape(color: Color) -> String {
+    format!("\x1b[38;2;{};{};{}m", color.r, color.g, color.b)
+}
+
+/// PNG swatch sheet: the wallpaper thumbnail next to color0-15 and the roles
+pub fn write_png(colors: &[Color], wallpaper: Option<&Path>, output_path: &Path) -> Result<()> {
+    let thumbnail = match wallpaper {
+        Some(path) => Some(flatten(&haishoku::open_image(path)?, colors[0])),
+        None => None,
+    };
+
+    render_swatch_sheet(colors, thumbnail.as_ref())
+        .save_with_format(output_path, image::ImageFormat::Png)
+        .map_err(|e| WalrusError::io(output_path, io::Error::other(e)))?;
+
+    Ok(())
+}
+
+// Transparent parts of the wallpaper show the sheet background
+fn flatten(image: &RgbaImage, background: Color) -> RgbImage {
+    RgbImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        let alpha = a as f32 / 255.0;
+        let blend = |c: u8, bg: u8| (c as f32 * alpha + bg as f32 * (1.0 - alpha)).round() as u8;
+        Rgb([
+            blend(r, background.r),
+            blend(g, background.g),
+            blend(b, background.b),
+        ])
+    })
+}
+
+pub fn render_swatch_sheet(colors: &[Color], wallpaper: Option<&RgbImage>) -> RgbImage {
+    let background = colors[0];
+    let foreground = *colors.get(15).unwrap_or(&colors[7]);
+    let outline = background.mix(&foreground, 0.2);
+
+    // Fit the wallpaper to the grid height, at most twice as wide as tall
+    let thumbnail = wallpaper
+        .filter(|image| image.width() > 0 && image.height() > 0)
+        .map(|image| {
+            let scale = (GRID_HEIGHT as f32 / image.height() as f32)
+                .min(2.0 * GRID_HEIGHT as f32 / image.width() as f32);
+            let width = ((image.width() as f32 * scale).round() as u32).max(1);
+            let height = ((image.height() as f32 * scale).round() as u32).max(1);
+            imageops::resize(image, width, height, imageops::FilterType::Triangle)
+        });
+
+    let grid_x = match &thumbnail {
+        Some(thumbnail) => SHEET_PADDING + thumbnail.width() + SHEET_PADDING,
+        None => SHEET_PADDING,
+    };
+    let mut sheet = RgbImage::from_pixel(
+        grid_x + GRID_WIDTH + SHEET_PADDING,
+        GRID_HEIGHT + 2 * SHEET_PADDING,
+        to_rgb(background),
+    );
+
+    if let Some(thumbnail) = &thumbnail {
+        imageops::replace(
+            &mut sheet,
+            thumbnail,
+            SHEET_PADDING as i64,
+            SHEET_PADDING as i64,
+        );
+    }
+
+    let mut swatches: Vec<(String, Color)> = colors
+        .iter()
+        .take(16)
+        .enumerate()
+        .map(|(i, color)| (format!("color{}", i), *color))
+        .collect();
+    swatches.extend(
+        TemplateGenerator::new(colors.to_vec(), false)
+            .roles()
+            .into_iter()
+            .map(|(name, color)| (name.to_string(), color)),
+    );
+
+    for (i, (name, color)) in swatches.iter().enumerate() {
+        let i = i as u32;
+        let row = i / SWATCHES_PER_ROW;
+        let x = grid_x + (i % SWATCHES_PER_ROW) * (SWATCH_WIDTH + SWATCH_GAP);
+        // The roles row sits below a wider gap
+        let mut y = SHEET_PADDING + row * (SWATCH_HEIGHT + SWATCH_GAP);
+        if row >= 2 {
+            y += SECTION_GAP - SWATCH_GAP;
+        }
+
+        fill_rect(&mut sheet, x, y, SWATCH_WIDTH, SWATCH_HEIGHT, outline);
+        fill_rect(
+            &mut sheet,
+            x + 2,
+            y + 2,
+            SWATCH_WIDTH - 4,
+            SWATCH_HEIGHT - 4,
+            *color,
+        );
+
+        // Label with whichever of background/foreground reads better
+        let label = if color.contrast_ratio(background) > color.contrast_ratio(foreground) {
+            background
+        } else {
+            foreground
+        };
+        let text_height = font::GLYPH_HEIGHT * TEXT_SCALE;
+        draw_text(&mut sheet, x + 8, y + 8, name, label);
+        draw_text(
+            &mut sheet,
+            x + 8,
+            y + SWATCH_HEIGHT - 8 - text_height,
+            &color.to_hex(),
+            label,
+        );
+    }
+
+    sheet
+}
+
+fn to_rgb(color: Color) -> Rgb<u8> {
+    Rgb([color.r, color.g, color.b])
+}
+
+fn fill_rect(image: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, color: Color) {
+    for py in y..(y + height).min(image.height()) {
+        for px in x..(x + width).min(image.width()) {
+            image.put_pixel(px, py, to_rgb(color));
+        }
+    }
+}
+
+fn draw_text(image: &mut RgbImage, x: u32, y: u32, text: &str, color: Color) {
+    for (i, c) in text.chars().enumerate() {
+        let glyph_x = x + i as u32 * (font::GLYPH_WIDTH + 1) * TEXT_SCALE;
+
+        for (row, bits) in font::glyph(c).iter().enumerate() {
+            for column in 0..font::GLYPH_WIDTH {
+                if bits & (1 << (font::GLYPH_WIDTH - 1 - column)) != 0 {
+                    fill_rect(
+                        image,
+                        glyph_x + column * TEXT_SCALE,
+                        y + row as u32 * TEXT_SCALE,
+                        TEXT_SCALE,
+                        TEXT_SCALE,
+                        color,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal_preview() {
+        let colors: Vec<Color> = (0..16)
+            .map(|i| Color::new(i * 17, i * 17, i * 17))
+            .collect();
+        let output = terminal_preview(&colors);
+
+        assert!(output.contains(" color0     #000000  1.00:1 "));
+        assert!(output.contains(" color15    #ffffff 21.00:1 AAA "));
+        assert!(output.contains("\x1b[48;2;0;0;0m\x1b[38;2;255;255;255m The quick"));
+        assert!(output.contains(" accent     #444444 "));
+        assert_eq!(output.lines().count(), 16 + 8 + 3);
+    }
+
+    #[test]
+    fn test_swatch_sheet_layout() {
+        let colors: Vec<Color> = (0..16)
+            .map(|i| Color::new(i * 17, 0, 255 - i * 17))
+            .collect();
+
+        let sheet = render_swatch_sheet(&colors, None);
+        assert_eq!(sheet.width(), GRID_WIDTH + 2 * SHEET_PADDING);
+        assert_eq!(sheet.height(), GRID_HEIGHT + 2 * SHEET_PADDING);
+
+        // Swatch interiors, clear of the labels, hold their colors
+        let center = |i: u32, row_offset: u32| {
+            let x = SHEET_PADDING + (i % 8) * (SWATCH_WIDTH + SWATCH_GAP) + SWATCH_WIDTH - 6;
+            let y = SHEET_PADDING
+                + (i / 8) * (SWATCH_HEIGHT + SWATCH_GAP)
+                + row_offset
+                + SWATCH_HEIGHT / 2;
+            *sheet.get_pixel(x, y)
+        };
+        assert_eq!(center(1, 0), Rgb([17, 0, 238]));
+        assert_eq!(center(15, 0), Rgb([255, 0, 0]));
+        // Roles row: accent is color4
+        assert_eq!(center(16 + 6, SECTION_GAP - SWATCH_GAP), Rgb([68, 0, 187]));
+
+        let wallpaper = RgbImage::from_pixel(400, 200, Rgb([1, 2, 3]));
+        let sheet = render_swatch_sheet(&colors, Some(&wallpaper));
+        assert_eq!(
+            sheet.width(),
+            3 * SHEET_PADDING + 2 * GRID_HEIGHT + GRID_WIDTH
+        );
+        assert_eq!(
+            *sheet.get_pixel(SHEET_PADDING + 10, SHEET_PADDING + 10),
+            Rgb([1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn test_font_has_label_glyphs() {
+        for c in "color0123456789#abcdefghijklmnopqrstuvwxyz".chars() {
+            assert!(font::glyph(c).iter().any(|row| *row != 0), "missing {}", c);
+        }
+        assert_eq!(font::glyph('a'), font::glyph('A'));
+    }
+}
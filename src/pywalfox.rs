@@ -0,0 +1,327 @@
+use regex::Regex;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, WalrusError};
+use crate::templates::json_escape;
+use crate::theme;
+use crate::wallpaper::shell_quote;
+
+// Daemon version reported to the extension, the extension refuses older daemons
+const DAEMON_VERSION: &str = "2.7";
+
+const MANIFEST_NAME: &str = "pywalfox";
+const EXTENSION_ID: &str = "pywalfox@frewacom.org";
+
+// Browsers never send native messages larger than 1 MiB
+const MAX_MESSAGE_LENGTH: usize = 1024 * 1024;
+
+/// Native messaging host for the pywalfox extension: messages are JSON
+/// prefixed with their length as a native-endian u32, on stdin and stdout
+pub fn run_native_host(output_dir: &Path) -> Result<()> {
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+
+    while let Some(message) = read_message(&mut stdin).map_err(|e| WalrusError::io("-", e))? {
+        let response = handle_message(&message, output_dir);
+        write_message(&mut stdout, &response).map_err(|e| WalrusError::io("-", e))?;
+    }
+
+    Ok(())
+}
+
+fn read_message(reader: &mut impl Read) -> io::Result<Option<String>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        // Browser closed the pipe
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+
+    let length = u32::from_ne_bytes(length) as usize;
+    if length > MAX_MESSAGE_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Message of {} bytes is over the 1 MiB limit", length),
+        ));
+    }
+
+    let mut message = vec![0u8; length];
+    reader.read_exact(&mut message)?;
+
+    String::from_utf8(message)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_message(writer: &mut impl Write, message: &str) -> io::Result<()> {
+    writer.write_all(&(message.len() as u32).to_ne_bytes())?;
+    writer.write_all(message.as_bytes())?;
+    writer.flush()
+}
+
+/// The value of a string field in a JSON object, with its escapes decoded
+pub fn string_field(message: &str, field: &str) -> Option<String> {
+    let re = Regex::new(&format!(r#""{}"\s*:\s*""#, regex::escape(field))).unwrap();
+    let start = re.find(message)?.end();
+
+    let mut value = String::new();
+    let mut chars = message[start..].chars();
+    loop {
+        match chars.next()? {
+            '"' => return Some(value),
+            '\\' => match chars.next()? {
+                c @ ('"' | '\\' | '/') => value.push(c),
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                'r' => value.push('\r'),
+                'b' => value.push('\u{8}'),
+                'f' => value.push('\u{c}'),
+                'u' => {
+                    let code = hex_escape(&mut chars)?;
+                    // Characters outside the BMP come as a surrogate pair
+                    let code = if (0xd800..0xdc00).contains(&code) {
+                        if chars.next()? != '\\' || chars.next()? != 'u' {
+                            return None;
+                        }
+                        let low = hex_escape(&mut chars)?;
+                        if !(0xdc00..0xe000).contains(&low) {
+                            return None;
+                        }
+                        0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00)
+                    } else {
+                        code
+                    };
+                    value.push(char::from_u32(code)?);
+                }
+                _ => return None,
+            },
+            c => value.push(c),
+        }
+    }
+}
+
+// The four hex digits of a \u escape
+fn hex_escape(chars: &mut std::str::Chars) -> Option<u32> {
+    let digits: String = chars.by_ref().take(4).collect();
+    if digits.len() != 4 {
+        return None;
+    }
+    u32::from_str_radix(&digits, 16).ok()
+}
+
+fn response(action: &str, success: bool, data: &str, error: Option<&str>) -> String {
+    let error = match error {
+        Some(error) => format!("\"{}\"", json_escape(error)),
+        None => "null".to_string(),
+    };
+
+    format!(
+        "{{\"action\": \"{}\", \"success\": {}, \"error\": {}, \"data\": {}}}",
+        json_escape(action),
+        success,
+        error,
+        data
+    )
+}
+
+pub fn handle_message(message: &str, output_dir: &Path) -> String {
+    let Some(action) = string_field(message, "action") else {
+        return response("", false, "null", Some("Message has no action"));
+    };
+
+    match action.as_str() {
+        "debug:version" => response(&action, true, &format!("\"{}\"", DAEMON_VERSION), None),
+        "action:colors" => match palette_data(output_dir) {
+            Ok(data) => response(&action, true, &data, None),
+            Err(e) => response(&action, false, "null", Some(&e.to_string())),
+        },
+        "theme:mode" => {
+            let mode = string_field(message, "mode").unwrap_or_default();
+            response(&action, true, &format!("\"{}\"", json_escape(&mode)), None)
+        }
+        // walrus does not touch the Firefox profile, it only writes colors-userChrome.css
+        "css:enable" | "css:disable" | "css:font:size" => response(
+            &action,
+            false,
+            "null",
+            Some(&format!(
+                "Not supported by walrus, import {} from your userChrome.css instead",
+                output_dir.join("colors-userChrome.css").display()
+            )),
+        ),
+        _ => response(
+            &action,
+            false,
+            "null",
+            Some(&format!("Unknown action: {}", action)),
+        ),
+    }
+}
+
+// { "colors": [...16 hex colors], "wallpaper": path } from the cached colors.json
+fn palette_data(output_dir: &Path) -> Result<String> {
+    let colors_path = output_dir.join("colors.json");
+    let content = fs::read_to_string(&colors_path).map_err(|e| WalrusError::io(&colors_path, e))?;
+
+    let colors = theme::parse_theme(&content)?;
+    let wallpaper = string_field(&content, "wallpaper").unwrap_or_default();
+
+    let colors: Vec<String> = colors
+        .iter()
+        .map(|color| format!("\"{}\"", color.to_hex()))
+        .collect();
+
+    Ok(format!(
+        "{{\"colors\": [{}], \"wallpaper\": \"{}\"}}",
+        colors.join(", "),
+        json_escape(&wallpaper)
+    ))
+}
+
+/// Register walrus as the pywalfox native host for Firefox, returns the manifest path
+pub fn install_native_host(output_dir: &Path) -> Result<PathBuf> {
+    let home_dir = dirs::home_dir().ok_or("Could not determine home directory")?;
+    let exe = std::env::current_exe().map_err(|e| WalrusError::io("walrus", e))?;
+
+    // Firefox passes the manifest path and extension id as arguments, so point
+    // the manifest at a wrapper script instead of the walrus binary itself
+    let host_dir = home_dir.join(".local/share/walrus");
+    fs::create_dir_all(&host_dir).map_err(|e| WalrusError::io(&host_dir, e))?;
+    let script_path = host_dir.join("pywalfox-host.sh");
+    fs::write(&script_path, host_script(&exe, output_dir))
+        .map_err(|e| WalrusError::io(&script_path, e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755))
+            .map_err(|e| WalrusError::io(&script_path, e))?;
+    }
+
+    let manifest_dir = home_dir.join(".mozilla/native-messaging-hosts");
+    fs::create_dir_all(&manifest_dir).map_err(|e| WalrusError::io(&manifest_dir, e))?;
+    let manifest_path = manifest_dir.join(format!("{}.json", MANIFEST_NAME));
+    fs::write(
+        &manifest_path,
+        format!(
+            "{{\n    \"name\": \"{}\",\n    \"description\": \"walrus pywalfox host\",\n    \"path\": \"{}\",\n    \"type\": \"stdio\",\n    \"allowed_extensions\": [\"{}\"]\n}}\n",
+            MANIFEST_NAME,
+            json_escape(&script_path.to_string_lossy()),
+            EXTENSION_ID
+        ),
+    )
+    .map_err(|e| WalrusError::io(&manifest_path, e))?;
+
+    Ok(manifest_path)
+}
+
+fn host_script(exe: &Path, output_dir: &Path) -> String {
+    format!(
+        "#!/bin/sh\nexec {} --output {} --pywalfox-host\n",
+        shell_quote(&exe.to_string_lossy()),
+        shell_quote(&output_dir.to_string_lossy())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_message_framing() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, "{\"action\": \"debug:version\"}").unwrap();
+
+        let mut reader = Cursor::new(buffer);
+        let message = read_message(&mut reader).unwrap();
+        assert_eq!(message.as_deref(), Some("{\"action\": \"debug:version\"}"));
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+
+        // Rejected from the length prefix, before the body is read
+        let oversized = ((MAX_MESSAGE_LENGTH + 1) as u32).to_ne_bytes();
+        let error = read_message(&mut Cursor::new(oversized)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_version_and_colors() {
+        let output_dir =
+            std::env::temp_dir().join(format!("walrus-pywalfox-{}", std::process::id()));
+        fs::create_dir_all(&output_dir).unwrap();
+
+        let version = handle_message("{\"action\": \"debug:version\"}", &output_dir);
+        assert!(version.contains("\"success\": true"));
+        assert!(version.contains(&format!("\"data\": \"{}\"", DAEMON_VERSION)));
+
+        let missing = handle_message("{\"action\": \"action:colors\"}", &output_dir);
+        assert!(missing.contains("\"success\": false"));
+
+        let mut json = String::from("{\"wallpaper\": \"/tmp/wall.png\", \"colors\": {");
+        for i in 0..16 {
+            json.push_str(&format!("\"color{}\": \"#0000{:02x}\",", i, i));
+        }
+        json.push_str("}}");
+        fs::write(output_dir.join("colors.json"), json).unwrap();
+
+        let colors = handle_message("{\"action\": \"action:colors\"}", &output_dir);
+        assert!(colors.contains("\"colors\": [\"#000000\", \"#000001\""));
+        assert!(colors.contains("\"wallpaper\": \"/tmp/wall.png\""));
+
+        let unknown = handle_message("{\"action\": \"unknown\"}", &output_dir);
+        assert!(unknown.contains("\"success\": false"));
+        assert!(unknown.contains("\"error\": \"Unknown action: unknown\""));
+
+        let no_action = handle_message("{\"mode\": \"dark\"}", &output_dir);
+        assert!(no_action.contains("\"success\": false"));
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_escaped_strings() {
+        let json =
+            r#"{"wallpaper": "C:\\walls\\\"night\" \u00e9t\u00e9 \ud83c\udf19.png", "x": 1}"#;
+        assert_eq!(
+            string_field(json, "wallpaper").as_deref(),
+            Some("C:\\walls\\\"night\" été 🌙.png")
+        );
+        assert_eq!(string_field(r#"{"mode": "a\qb"}"#, "mode"), None);
+        assert_eq!(string_field(r#"{"mode": "unterminated"#, "mode"), None);
+
+        let output_dir =
+            std::env::temp_dir().join(format!("walrus-pywalfox-escape-{}", std::process::id()));
+        fs::create_dir_all(&output_dir).unwrap();
+        let mut colors_json = format!(
+            "{{\"wallpaper\": \"{}\", \"colors\": {{",
+            json_escape("/tmp/a \"b\"\\c.png")
+        );
+        for i in 0..16 {
+            colors_json.push_str(&format!("\"color{}\": \"#0000{:02x}\",", i, i));
+        }
+        colors_json.push_str("}}");
+        fs::write(output_dir.join("colors.json"), colors_json).unwrap();
+
+        let data = palette_data(&output_dir).unwrap();
+        assert!(data.ends_with(r#""wallpaper": "/tmp/a \"b\"\\c.png"}"#));
+        assert_eq!(
+            string_field(&data, "wallpaper").as_deref(),
+            Some("/tmp/a \"b\"\\c.png")
+        );
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_host_script_quotes_paths() {
+        let script = host_script(
+            Path::new("/opt/it's/walrus"),
+            Path::new("/home/me/'; rm -rf ~; '"),
+        );
+        assert_eq!(
+            script,
+            "#!/bin/sh\nexec '/opt/it'\\''s/walrus' --output '/home/me/'\\''; rm -rf ~; '\\''' --pywalfox-host\n"
+        );
+    }
+}
@@ -1,14 +1,18 @@
+//! Escape sequences that recolor running terminals
+
 use crate::color::Color;
-use glob;
+use crate::error::{Result, WalrusError};
 use std::fs;
+use std::path::PathBuf;
 
-// Term sequences gen
+/// Terminal escape sequences applying a palette, like pywal's sequences file
 pub struct SequenceGenerator {
     colors: Vec<Color>,
     alpha: u8,
 }
 
 impl SequenceGenerator {
+    /// Sequences for a 16 color palette, alpha 100 is an opaque background
     pub fn new(colors: Vec<Color>, alpha: u8) -> Self {
         SequenceGenerator { colors, alpha }
     }
@@ -31,9 +35,9 @@ impl SequenceGenerator {
         #[cfg(not(target_os = "macos"))]
         {
             if (index == 11 || index == 708) && self.alpha != 100 {
-                return format!("\x1b]{};[{}]{}\x1b\\", index, self.alpha, color.to_hex());
+                format!("\x1b]{};[{}]{}\x1b\\", index, self.alpha, color.to_hex())
             } else {
-                return format!("\x1b]{};{}\x1b\\", index, color.to_hex());
+                format!("\x1b]{};{}\x1b\\", index, color.to_hex())
             }
         }
     }
@@ -95,12 +99,15 @@ impl SequenceGenerator {
         sequences
     }
 
+    /// OSC escape sequences setting the palette, background, foreground and cursor
     pub fn generate_sequences(&self, vte_fix: bool) -> String {
         self.create_sequences(vte_fix)
     }
 
-    // Send sequences to all open terminals
-    pub fn send_sequences_to_terminals(&self, vte_fix: bool) -> std::io::Result<()> {
+    /// Send sequences to all open terminals. Terminals that can't be written,
+    /// such as another user's, are warned about; the first failure is only
+    /// returned when no terminal could be written at all.
+    pub fn send_sequences_to_terminals(&self, vte_fix: bool) -> Result<()> {
         let sequences = self.generate_sequences(vte_fix);
 
         #[cfg(target_os = "macos")]
@@ -109,16 +116,50 @@ impl SequenceGenerator {
         #[cfg(not(target_os = "macos"))]
         let tty_pattern = "/dev/pts/[0-9]*";
 
-        for entry in glob::glob(tty_pattern).expect("Failed to read tty pattern") {
-            if let Ok(path) = entry {
-                // Try to write sequences to the terminal
-                if let Err(e) = fs::write(&path, &sequences) {
-                    eprintln!("Failed to write to {}: {}", path.display(), e);
-                }
-            }
+        let terminals = glob::glob(tty_pattern)
+            .expect("Failed to read tty pattern")
+            .flatten();
+        write_to_terminals(terminals, &sequences)
+    }
+}
+
+fn write_to_terminals(terminals: impl Iterator<Item = PathBuf>, sequences: &str) -> Result<()> {
+    let mut written = false;
+    let mut failures = Vec::new();
+    for path in terminals {
+        match fs::write(&path, sequences) {
+            Ok(()) => written = true,
+            Err(source) => failures.push(WalrusError::TerminalWrite { path, source }),
         }
+    }
 
-        Ok(())
+    let mut failures = failures.into_iter();
+    let first = if written { None } else { failures.next() };
+    for failure in failures {
+        eprintln!("Warning: {}", failure);
     }
+
+    first.map_or(Ok(()), Err)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unwritable_terminals_are_warnings() {
+        let dir = std::env::temp_dir().join(format!("walrus-ttys-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let writable = dir.join("1");
+        let missing = dir.join("missing/0");
+
+        write_to_terminals([missing.clone(), writable.clone()].into_iter(), "seq").unwrap();
+        assert_eq!(fs::read_to_string(&writable).unwrap(), "seq");
+
+        let error = write_to_terminals([missing.clone()].into_iter(), "seq").unwrap_err();
+        assert!(matches!(error, WalrusError::TerminalWrite { path, .. } if path == missing));
+
+        assert!(write_to_terminals(std::iter::empty(), "seq").is_ok());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,12 +1,38 @@
+//! Built-in output formats: shell, CSS, JSON, terminal, editor and desktop configs
+
 use crate::color::Color;
 
+mod browser;
+mod desktop;
+mod editors;
+mod terminals;
+mod toolkits;
+
+// Names of color0-7 as used by most terminal configs
+const ANSI_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Renders a palette into the built-in output formats
 pub struct TemplateGenerator {
     colors: Vec<Color>,
     strip_hash: bool,
 }
 
 impl TemplateGenerator {
+    /// Generator for a 16 color palette, strip_hash drops the # in the shell,
+    /// CSS and Xresources outputs
+    ///
+    /// # Panics
+    ///
+    /// If colors holds fewer than the 16 terminal colors, as every generated
+    /// palette and theme does
     pub fn new(colors: Vec<Color>, strip_hash: bool) -> Self {
+        assert!(
+            colors.len() >= 16,
+            "TemplateGenerator needs 16 colors, got {}",
+            colors.len()
+        );
         TemplateGenerator { colors, strip_hash }
     }
 
@@ -18,6 +44,105 @@ impl TemplateGenerator {
         }
     }
 
+    fn background(&self) -> &Color {
+        &self.colors[0]
+    }
+
+    fn foreground(&self) -> &Color {
+        self.colors.get(15).unwrap_or(&self.colors[7])
+    }
+
+    fn selection(&self) -> &Color {
+        &self.colors[8]
+    }
+
+    fn comment(&self) -> &Color {
+        &self.colors[8]
+    }
+
+    // Slightly raised background for panels, popups and the cursor line
+    fn surface(&self) -> Color {
+        self.background().mix(self.foreground(), 0.08)
+    }
+
+    fn accent(&self) -> &Color {
+        &self.colors[4]
+    }
+
+    fn urgent(&self) -> &Color {
+        &self.colors[1]
+    }
+
+    // Whichever of background/foreground reads better on top of color
+    fn text_on(&self, color: &Color) -> &Color {
+        let (dark, light) = if self.is_light() {
+            (self.foreground(), self.background())
+        } else {
+            (self.background(), self.foreground())
+        };
+
+        if color.to_yiq() > 0.5 { dark } else { light }
+    }
+
+    fn is_light(&self) -> bool {
+        self.background().to_yiq() > self.foreground().to_yiq()
+    }
+
+    // Semantic roles shared by the built-in outputs, as (name, color)
+    pub(crate) fn roles(&self) -> Vec<(&'static str, Color)> {
+        vec![
+            ("background", *self.background()),
+            ("foreground", *self.foreground()),
+            ("cursor", *self.foreground()),
+            ("selection", *self.selection()),
+            ("comment", *self.comment()),
+            ("surface", self.surface()),
+            ("accent", *self.accent()),
+            ("urgent", *self.urgent()),
+        ]
+    }
+
+    /// Built-in outputs written next to colors.sh, as (file name, content)
+    pub fn generate_builtin_templates(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("colors-kitty.conf", self.generate_kitty_template()),
+            ("colors-alacritty.toml", self.generate_alacritty_template()),
+            ("colors-foot.ini", self.generate_foot_template()),
+            ("colors-wezterm.toml", self.generate_wezterm_template()),
+            ("colors-ghostty", self.generate_ghostty_template()),
+            (
+                "colors-konsole.colorscheme",
+                self.generate_konsole_template(),
+            ),
+            (
+                "colors-windows-terminal.json",
+                self.generate_windows_terminal_template(),
+            ),
+            ("colors-nvim.lua", self.generate_neovim_template()),
+            ("colors-vim.vim", self.generate_vim_template()),
+            ("colors-helix.toml", self.generate_helix_template()),
+            (
+                "vscode/package.json",
+                self.generate_vscode_package_template(),
+            ),
+            (
+                "vscode/themes/walrus-color-theme.json",
+                self.generate_vscode_theme_template(),
+            ),
+            ("colors-gtk.css", self.generate_gtk_template()),
+            ("colors-qt.conf", self.generate_qt_template()),
+            ("colors-kde.colors", self.generate_kde_template()),
+            ("colors-waybar.css", self.generate_waybar_template()),
+            ("colors-mako", self.generate_mako_template()),
+            ("colors-dunstrc", self.generate_dunst_template()),
+            ("colors-rofi.rasi", self.generate_rofi_template()),
+            ("colors-fuzzel.ini", self.generate_fuzzel_template()),
+            ("colors-swaylock", self.generate_swaylock_template()),
+            ("colors-userChrome.css", self.generate_userchrome_template()),
+        ]
+    }
+
+    /// colors.sh, exported shell variables
     pub fn generate_shell_template(&self) -> String {
         let mut output = String::new();
         output.push_str("#!/bin/bash\n");
@@ -47,6 +172,7 @@ impl TemplateGenerator {
         output
     }
 
+    /// colors.css, CSS custom properties on :root
     pub fn generate_css_template(&self) -> String {
         let mut output = String::new();
         output.push_str("/* Pywal-style CSS variables generated with haishoku algorithm */\n");
@@ -77,6 +203,7 @@ impl TemplateGenerator {
         output
     }
 
+    /// colors.json in the stripped hex format read by Thorn
     pub fn generate_json_template(&self) -> String {
         let mut output = String::new();
         output.push_str("{\n");
@@ -98,6 +225,51 @@ impl TemplateGenerator {
         output
     }
 
+    /// Full pywal colors.json schema, read by pywalfox, pywal-discord and friends
+    pub fn generate_pywal_json_template(&self, wallpaper_path: &str, alpha: u8) -> String {
+        let background = &self.colors[0];
+        let foreground = self.colors.get(15).unwrap_or(&self.colors[7]);
+
+        let mut output = String::new();
+        output.push_str("{\n");
+        output.push_str(&format!(
+            "    \"wallpaper\": \"{}\",\n",
+            json_escape(wallpaper_path)
+        ));
+        output.push_str(&format!("    \"alpha\": \"{}\",\n\n", alpha));
+
+        output.push_str("    \"special\": {\n");
+        output.push_str(&format!(
+            "        \"background\": \"{}\",\n",
+            background.to_hex()
+        ));
+        output.push_str(&format!(
+            "        \"foreground\": \"{}\",\n",
+            foreground.to_hex()
+        ));
+        output.push_str(&format!(
+            "        \"cursor\": \"{}\"\n",
+            foreground.to_hex()
+        ));
+        output.push_str("    },\n");
+
+        output.push_str("    \"colors\": {\n");
+        for (i, color) in self.colors.iter().enumerate() {
+            let comma = if i == self.colors.len() - 1 { "" } else { "," };
+            output.push_str(&format!(
+                "        \"color{}\": \"{}\"{}\n",
+                i,
+                color.to_hex(),
+                comma
+            ));
+        }
+
+        output.push_str("    }\n");
+        output.push_str("}\n");
+        output
+    }
+
+    /// colors.Xresources
     pub fn generate_xresources_template(&self) -> String {
         let mut output = String::new();
         output.push_str("! Pywal-style Xresources generated with haishoku algorithm\n");
@@ -123,6 +295,7 @@ impl TemplateGenerator {
         output
     }
 
+    /// colors.scss, SCSS variables
     pub fn generate_scss_template(&self, wallpaper_path: &str) -> String {
         let mut output = String::new();
         output.push_str("// SCSS Variables\n");
@@ -157,5 +330,80 @@ impl TemplateGenerator {
 
         output
     }
+
+    /// Base16/Base24 scheme YAML in the tinted-theming format
+    pub fn generate_base16_template(&self, scheme: &[Color]) -> String {
+        let mut output = String::new();
+        output.push_str("scheme: \"Walrus\"\n");
+        output.push_str("author: \"walrus\"\n");
+
+        for (i, color) in scheme.iter().enumerate() {
+            output.push_str(&format!("base{:02X}: \"{}\"\n", i, color.to_hex_stripped()));
+        }
+
+        output
+    }
+}
+
+// Contents of a JSON string literal, control characters included
+pub(crate) fn json_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\u{c}' => escaped.push_str("\\f"),
+            c if c < ' ' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) fn test_colors() -> Vec<Color> {
+        (0..16)
+            .map(|i| Color::new(i * 16, 0, 255 - i * 16))
+            .collect()
+    }
+
+    pub(super) fn template_gen() -> TemplateGenerator {
+        TemplateGenerator::new(test_colors(), false)
+    }
+
+    #[test]
+    fn test_pywal_json_schema() {
+        let template_gen = TemplateGenerator::new(test_colors(), false);
+        let json = template_gen.generate_pywal_json_template("/tmp/wall \"1\".png", 100);
+
+        assert!(json.contains("\"wallpaper\": \"/tmp/wall \\\"1\\\".png\""));
+        assert!(json.contains("\"alpha\": \"100\""));
+        assert!(json.contains("\"background\": \"#0000ff\""));
+        assert!(json.contains("\"cursor\": \"#f0000f\""));
+        assert!(json.contains("\"color15\": \"#f0000f\""));
+
+        let parsed = crate::theme::parse_theme(&json).unwrap();
+        assert_eq!(parsed, test_colors());
+    }
+
+    #[test]
+    #[should_panic(expected = "TemplateGenerator needs 16 colors, got 8")]
+    fn test_short_palette_rejected() {
+        TemplateGenerator::new(test_colors()[..8].to_vec(), false);
+    }
+
+    #[test]
+    fn test_json_escape_control_characters() {
+        assert_eq!(
+            json_escape("a\"b\\c\nd\te\r\u{8}\u{c}\u{1}\u{1f} é"),
+            "a\\\"b\\\\c\\nd\\te\\r\\b\\f\\u0001\\u001f é"
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use super::TemplateGenerator;
+
+impl TemplateGenerator {
+    /// Firefox browser chrome colors, import from the profile's chrome/userChrome.css
+    pub fn generate_userchrome_template(&self) -> String {
+        let background = self.background().to_hex();
+        let foreground = self.foreground().to_hex();
+        let surface = self.surface().to_hex();
+        let accent = self.accent().to_hex();
+        let selection = self.selection().to_hex();
+
+        let mut output = String::new();
+        output.push_str("/* Firefox userChrome.css colors generated by walrus */\n");
+        output.push_str(
+            "/* Use with: @import url(\"file:///path/to/colors-userChrome.css\"); at the top of userChrome.css */\n",
+        );
+        output.push_str(
+            "/* Requires toolkit.legacyUserProfileCustomizations.stylesheets = true in about:config */\n\n",
+        );
+
+        output.push_str(":root {\n");
+        let variables = [
+            ("--walrus-background", &background),
+            ("--walrus-foreground", &foreground),
+            ("--walrus-surface", &surface),
+            ("--walrus-accent", &accent),
+            ("--walrus-selection", &selection),
+            ("--lwt-accent-color", &background),
+            ("--lwt-text-color", &foreground),
+            ("--toolbar-bgcolor", &surface),
+            ("--toolbar-color", &foreground),
+            ("--toolbar-field-background-color", &background),
+            ("--toolbar-field-color", &foreground),
+            ("--toolbar-field-focus-background-color", &background),
+            ("--toolbar-field-focus-color", &foreground),
+            ("--tab-selected-bgcolor", &surface),
+            ("--tab-selected-textcolor", &foreground),
+            ("--arrowpanel-background", &surface),
+            ("--arrowpanel-color", &foreground),
+            ("--focus-outline-color", &accent),
+            ("--urlbarView-highlight-background", &selection),
+        ];
+        for (name, value) in variables {
+            output.push_str(&format!("  {}: {} !important;\n", name, value));
+        }
+        output.push_str("}\n\n");
+
+        output.push_str("#navigator-toolbox {\n");
+        output.push_str("  background-color: var(--walrus-background) !important;\n");
+        output.push_str("  color: var(--walrus-foreground) !important;\n");
+        output.push_str("}\n\n");
+
+        output.push_str(".tab-background[selected] {\n");
+        output.push_str("  background-color: var(--walrus-surface) !important;\n");
+        output.push_str("  border-top: 2px solid var(--walrus-accent) !important;\n");
+        output.push_str("}\n");
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::template_gen;
+
+    #[test]
+    fn test_userchrome_colors() {
+        let template_gen = template_gen();
+        let output = template_gen.generate_userchrome_template();
+        let surface = template_gen.surface().to_hex();
+
+        assert!(output.contains("  --walrus-background: #0000ff !important;\n"));
+        assert!(output.contains("  --walrus-foreground: #f0000f !important;\n"));
+        assert!(output.contains("  --walrus-accent: #4000bf !important;\n"));
+        assert!(output.contains("  --walrus-selection: #80007f !important;\n"));
+        assert!(output.contains(&format!("  --walrus-surface: {} !important;\n", surface)));
+        assert!(output.contains(&format!("  --toolbar-bgcolor: {} !important;\n", surface)));
+        assert!(output.contains("  --focus-outline-color: #4000bf !important;\n"));
+        assert!(output.starts_with("/* Firefox userChrome.css colors"));
+    }
+}
@@ -0,0 +1,222 @@
+use super::TemplateGenerator;
+use crate::color::Color;
+
+impl TemplateGenerator {
+    /// Waybar @define-color definitions for style.css
+    pub fn generate_waybar_template(&self) -> String {
+        let mut output = String::new();
+        output.push_str("/* Waybar colors generated by walrus */\n");
+        output.push_str(
+            "/* Use with: @import \"../../.cache/walrus/colors-waybar.css\"; in style.css */\n\n",
+        );
+
+        let roles = [
+            ("background", self.background().to_hex()),
+            ("foreground", self.foreground().to_hex()),
+            ("surface", self.surface().to_hex()),
+            ("accent", self.accent().to_hex()),
+            ("urgent", self.urgent().to_hex()),
+            ("comment", self.comment().to_hex()),
+        ];
+        for (name, value) in roles {
+            output.push_str(&format!("@define-color {} {};\n", name, value));
+        }
+
+        output.push('\n');
+        for (i, color) in self.colors.iter().enumerate() {
+            output.push_str(&format!("@define-color color{} {};\n", i, color.to_hex()));
+        }
+
+        output
+    }
+
+    /// Mako config to include from ~/.config/mako/config
+    pub fn generate_mako_template(&self) -> String {
+        let mut output = String::new();
+        output.push_str("# Mako colors generated by walrus\n");
+        output.push_str("# Use with: include=~/.cache/walrus/colors-mako\n\n");
+
+        output.push_str(&format!(
+            "background-color={}\n",
+            self.background().to_hex()
+        ));
+        output.push_str(&format!("text-color={}\n", self.foreground().to_hex()));
+        output.push_str(&format!("border-color={}\n", self.accent().to_hex()));
+        output.push_str(&format!(
+            "progress-color=over {}\n\n",
+            self.selection().to_hex()
+        ));
+
+        output.push_str("[urgency=low]\n");
+        output.push_str(&format!("border-color={}\n\n", self.comment().to_hex()));
+
+        output.push_str("[urgency=high]\n");
+        output.push_str(&format!("border-color={}\n", self.urgent().to_hex()));
+
+        output
+    }
+
+    /// Dunst drop-in for ~/.config/dunst/dunstrc.d/
+    pub fn generate_dunst_template(&self) -> String {
+        let mut output = String::new();
+        output.push_str("# Dunst colors generated by walrus\n");
+        output.push_str("# Copy to ~/.config/dunst/dunstrc.d/ to load it after dunstrc\n\n");
+
+        output.push_str("[global]\n");
+        output.push_str(&format!(
+            "    frame_color = \"{}\"\n",
+            self.accent().to_hex()
+        ));
+        output.push_str("    separator_color = frame\n");
+
+        let urgencies = [
+            ("urgency_low", self.comment()),
+            ("urgency_normal", self.accent()),
+            ("urgency_critical", self.urgent()),
+        ];
+        for (section, frame) in urgencies {
+            output.push_str(&format!("\n[{}]\n", section));
+            output.push_str(&format!(
+                "    background = \"{}\"\n",
+                self.background().to_hex()
+            ));
+            output.push_str(&format!(
+                "    foreground = \"{}\"\n",
+                self.foreground().to_hex()
+            ));
+            output.push_str(&format!("    frame_color = \"{}\"\n", frame.to_hex()));
+        }
+
+        output
+    }
+
+    /// Rofi colors are #rrggbbaa
+    pub fn generate_rofi_template(&self) -> String {
+        let mut output = String::new();
+        output.push_str("/* Rofi colors generated by walrus */\n");
+        output.push_str("/* Use with: @import \"~/.cache/walrus/colors-rofi.rasi\" */\n\n");
+
+        output.push_str("* {\n");
+        let roles = [
+            ("background", *self.background()),
+            ("background-alt", self.surface()),
+            ("foreground", *self.foreground()),
+            ("selected", *self.accent()),
+            ("selected-foreground", *self.text_on(self.accent())),
+            ("active", self.colors[2]),
+            ("urgent", *self.urgent()),
+            ("border-color", *self.accent()),
+        ];
+        for (name, color) in roles {
+            output.push_str(&format!("    {}: {};\n", name, color.to_hex_alpha(0xff)));
+        }
+        output.push_str("}\n");
+
+        output
+    }
+
+    /// Fuzzel colors are rrggbbaa without a leading #
+    pub fn generate_fuzzel_template(&self) -> String {
+        let rgba = |color: &Color| color.to_hex_alpha(0xff)[1..].to_string();
+
+        let mut output = String::new();
+        output.push_str("# Fuzzel colors generated by walrus\n");
+        output.push_str("# Use with: include=~/.cache/walrus/colors-fuzzel.ini\n\n");
+
+        output.push_str("[colors]\n");
+        let roles = [
+            ("background", *self.background()),
+            ("text", *self.foreground()),
+            ("prompt", *self.accent()),
+            ("input", *self.foreground()),
+            ("placeholder", *self.comment()),
+            ("match", *self.accent()),
+            ("selection", *self.accent()),
+            ("selection-text", *self.text_on(self.accent())),
+            ("selection-match", *self.urgent()),
+            ("border", *self.accent()),
+        ];
+        for (name, color) in roles {
+            output.push_str(&format!("{}={}\n", name, rgba(&color)));
+        }
+
+        output
+    }
+
+    /// Swaylock colors are rrggbb or rrggbbaa, without a leading #
+    pub fn generate_swaylock_template(&self) -> String {
+        let rgba = |color: &Color| color.to_hex_alpha(0xff)[1..].to_string();
+        let transparent = "00000000".to_string();
+
+        let mut output = String::new();
+        output.push_str("# Swaylock colors generated by walrus\n");
+        output.push_str("# Use with: swaylock -C ~/.cache/walrus/colors-swaylock\n\n");
+
+        let options = [
+            ("color", rgba(self.background())),
+            ("inside-color", rgba(self.background())),
+            ("inside-clear-color", rgba(self.background())),
+            ("inside-ver-color", rgba(self.background())),
+            ("inside-wrong-color", rgba(self.background())),
+            ("ring-color", rgba(self.accent())),
+            ("ring-clear-color", rgba(&self.colors[2])),
+            ("ring-ver-color", rgba(&self.colors[3])),
+            ("ring-wrong-color", rgba(self.urgent())),
+            ("key-hl-color", rgba(self.foreground())),
+            ("bs-hl-color", rgba(self.urgent())),
+            ("text-color", rgba(self.foreground())),
+            ("text-clear-color", rgba(self.foreground())),
+            ("text-ver-color", rgba(self.foreground())),
+            ("text-wrong-color", rgba(self.urgent())),
+            ("line-color", transparent.clone()),
+            ("separator-color", transparent),
+        ];
+        for (name, value) in options {
+            output.push_str(&format!("{}={}\n", name, value));
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::template_gen;
+
+    #[test]
+    fn test_waybar_colors() {
+        let output = template_gen().generate_waybar_template();
+        assert!(output.contains("@define-color background #0000ff;\n"));
+        assert!(output.contains("@define-color accent #4000bf;\n"));
+        assert!(output.contains("@define-color color15 #f0000f;\n"));
+    }
+
+    #[test]
+    fn test_mako_and_dunst_colors() {
+        let mako = template_gen().generate_mako_template();
+        assert!(mako.contains("background-color=#0000ff\n"));
+        assert!(mako.contains("[urgency=high]\nborder-color=#1000ef\n"));
+
+        let dunst = template_gen().generate_dunst_template();
+        assert!(dunst.contains("[urgency_critical]\n"));
+        assert!(dunst.contains("    frame_color = \"#1000ef\"\n"));
+    }
+
+    #[test]
+    fn test_rofi_uses_rgba_hex() {
+        let output = template_gen().generate_rofi_template();
+        assert!(output.contains("    background: #0000ffff;\n"));
+        assert!(output.contains("    selected: #4000bfff;\n"));
+    }
+
+    #[test]
+    fn test_fuzzel_and_swaylock_strip_hash() {
+        let fuzzel = template_gen().generate_fuzzel_template();
+        assert!(fuzzel.contains("[colors]\nbackground=0000ffff\n"));
+        assert!(fuzzel.lines().all(|line| !line.contains("=#")));
+
+        let swaylock = template_gen().generate_swaylock_template();
+        assert!(swaylock.contains("ring-wrong-color=1000efff\n"));
+        assert!(swaylock.lines().all(|line| !line.contains("=#")));
+    }
+}
@@ -0,0 +1,523 @@
+use super::{ANSI_NAMES, TemplateGenerator};
+use crate::color::Color;
+
+#[derive(Clone, Copy)]
+enum Role {
+    Background,
+    Foreground,
+    Surface,
+    Selection,
+    Comment,
+    Palette(usize),
+}
+
+// (group, fg, bg, style) shared by the Neovim and Vim colorschemes
+const HIGHLIGHTS: &[(&str, Option<Role>, Option<Role>, &str)] = &[
+    ("Normal", Some(Role::Foreground), Some(Role::Background), ""),
+    (
+        "NormalFloat",
+        Some(Role::Foreground),
+        Some(Role::Surface),
+        "",
+    ),
+    ("Comment", Some(Role::Comment), None, "italic"),
+    ("Constant", Some(Role::Palette(3)), None, ""),
+    ("String", Some(Role::Palette(2)), None, ""),
+    ("Character", Some(Role::Palette(2)), None, ""),
+    ("Number", Some(Role::Palette(3)), None, ""),
+    ("Boolean", Some(Role::Palette(3)), None, ""),
+    ("Identifier", Some(Role::Foreground), None, ""),
+    ("Function", Some(Role::Palette(4)), None, ""),
+    ("Statement", Some(Role::Palette(5)), None, ""),
+    ("Keyword", Some(Role::Palette(5)), None, ""),
+    ("Operator", Some(Role::Palette(6)), None, ""),
+    ("PreProc", Some(Role::Palette(6)), None, ""),
+    ("Type", Some(Role::Palette(3)), None, ""),
+    ("Special", Some(Role::Palette(6)), None, ""),
+    ("Delimiter", Some(Role::Foreground), None, ""),
+    ("Underlined", Some(Role::Palette(4)), None, "underline"),
+    ("Error", Some(Role::Palette(1)), None, "bold"),
+    (
+        "Todo",
+        Some(Role::Background),
+        Some(Role::Palette(3)),
+        "bold",
+    ),
+    ("ErrorMsg", Some(Role::Palette(1)), None, ""),
+    ("WarningMsg", Some(Role::Palette(3)), None, ""),
+    ("Cursor", Some(Role::Background), Some(Role::Foreground), ""),
+    ("CursorLine", None, Some(Role::Surface), ""),
+    ("CursorLineNr", Some(Role::Foreground), None, "bold"),
+    ("LineNr", Some(Role::Comment), None, ""),
+    ("SignColumn", None, Some(Role::Background), ""),
+    ("Visual", None, Some(Role::Selection), ""),
+    ("Search", Some(Role::Background), Some(Role::Palette(3)), ""),
+    (
+        "IncSearch",
+        Some(Role::Background),
+        Some(Role::Palette(1)),
+        "",
+    ),
+    ("MatchParen", Some(Role::Palette(4)), None, "bold"),
+    ("Pmenu", Some(Role::Foreground), Some(Role::Surface), ""),
+    (
+        "PmenuSel",
+        Some(Role::Background),
+        Some(Role::Palette(4)),
+        "",
+    ),
+    (
+        "StatusLine",
+        Some(Role::Foreground),
+        Some(Role::Surface),
+        "",
+    ),
+    ("StatusLineNC", Some(Role::Comment), Some(Role::Surface), ""),
+    ("TabLine", Some(Role::Comment), Some(Role::Surface), ""),
+    (
+        "TabLineSel",
+        Some(Role::Background),
+        Some(Role::Palette(4)),
+        "",
+    ),
+    ("VertSplit", Some(Role::Comment), None, ""),
+    ("Folded", Some(Role::Comment), Some(Role::Surface), ""),
+    ("NonText", Some(Role::Comment), None, ""),
+    ("Title", Some(Role::Palette(4)), None, "bold"),
+    ("DiffAdd", Some(Role::Palette(2)), Some(Role::Surface), ""),
+    (
+        "DiffChange",
+        Some(Role::Palette(3)),
+        Some(Role::Surface),
+        "",
+    ),
+    (
+        "DiffDelete",
+        Some(Role::Palette(1)),
+        Some(Role::Surface),
+        "",
+    ),
+    (
+        "DiffText",
+        Some(Role::Background),
+        Some(Role::Palette(3)),
+        "",
+    ),
+];
+
+// Neovim-only groups, linked to the groups above
+const NEOVIM_LINKS: &[(&str, &str)] = &[
+    ("WinSeparator", "VertSplit"),
+    ("FloatBorder", "VertSplit"),
+    ("DiagnosticError", "ErrorMsg"),
+    ("DiagnosticWarn", "WarningMsg"),
+    ("DiagnosticInfo", "Function"),
+    ("DiagnosticHint", "Special"),
+    ("@variable", "Identifier"),
+    ("@comment", "Comment"),
+];
+
+impl TemplateGenerator {
+    fn role(&self, role: Role) -> Color {
+        match role {
+            Role::Background => *self.background(),
+            Role::Foreground => *self.foreground(),
+            Role::Surface => self.surface(),
+            Role::Selection => *self.selection(),
+            Role::Comment => *self.comment(),
+            Role::Palette(i) => self.colors[i],
+        }
+    }
+
+    // Terminal palette index for cterm colors
+    fn role_cterm(&self, role: Role) -> String {
+        match role {
+            Role::Background | Role::Surface => "0".to_string(),
+            Role::Foreground => "15".to_string(),
+            Role::Selection | Role::Comment => "8".to_string(),
+            Role::Palette(i) => i.to_string(),
+        }
+    }
+
+    fn background_mode(&self) -> &'static str {
+        if self.is_light() { "light" } else { "dark" }
+    }
+
+    /// Neovim colorscheme in Lua
+    pub fn generate_neovim_template(&self) -> String {
+        let mut output = String::new();
+        output.push_str("-- Neovim colorscheme generated by walrus\n");
+        output.push_str(
+            "-- Link to ~/.config/nvim/colors/walrus.lua and run :colorscheme walrus\n\n",
+        );
+
+        output.push_str("vim.cmd(\"highlight clear\")\n");
+        output.push_str("if vim.fn.exists(\"syntax_on\") == 1 then\n");
+        output.push_str("  vim.cmd(\"syntax reset\")\n");
+        output.push_str("end\n\n");
+        output.push_str("vim.o.termguicolors = true\n");
+        output.push_str(&format!(
+            "vim.o.background = \"{}\"\n",
+            self.background_mode()
+        ));
+        output.push_str("vim.g.colors_name = \"walrus\"\n\n");
+
+        output.push_str("local hl = vim.api.nvim_set_hl\n\n");
+        for (group, fg, bg, style) in HIGHLIGHTS {
+            let mut attrs = Vec::new();
+            if let Some(fg) = fg {
+                attrs.push(format!("fg = \"{}\"", self.role(*fg).to_hex()));
+            }
+            if let Some(bg) = bg {
+                attrs.push(format!("bg = \"{}\"", self.role(*bg).to_hex()));
+            }
+            if !style.is_empty() {
+                attrs.push(format!("{} = true", style));
+            }
+            output.push_str(&format!(
+                "hl(0, \"{}\", {{ {} }})\n",
+                group,
+                attrs.join(", ")
+            ));
+        }
+
+        for (group, link) in NEOVIM_LINKS {
+            output.push_str(&format!(
+                "hl(0, \"{}\", {{ link = \"{}\" }})\n",
+                group, link
+            ));
+        }
+
+        output.push('\n');
+        for (i, color) in self.colors.iter().enumerate() {
+            output.push_str(&format!(
+                "vim.g.terminal_color_{} = \"{}\"\n",
+                i,
+                color.to_hex()
+            ));
+        }
+
+        output
+    }
+
+    /// Vim colorscheme
+    pub fn generate_vim_template(&self) -> String {
+        let mut output = String::new();
+        output.push_str("\" Vim colorscheme generated by walrus\n");
+        output.push_str("\" Link to ~/.vim/colors/walrus.vim and run :colorscheme walrus\n\n");
+
+        output.push_str("hi clear\n");
+        output.push_str("if exists(\"syntax_on\")\n");
+        output.push_str("  syntax reset\n");
+        output.push_str("endif\n\n");
+        output.push_str(&format!("set background={}\n", self.background_mode()));
+        output.push_str("let g:colors_name = \"walrus\"\n\n");
+
+        for (group, fg, bg, style) in HIGHLIGHTS {
+            let style = if style.is_empty() { "NONE" } else { style };
+            let mut line = format!("hi {}", group);
+
+            if let Some(fg) = fg {
+                line.push_str(&format!(
+                    " guifg={} ctermfg={}",
+                    self.role(*fg).to_hex(),
+                    self.role_cterm(*fg)
+                ));
+            }
+            if let Some(bg) = bg {
+                line.push_str(&format!(
+                    " guibg={} ctermbg={}",
+                    self.role(*bg).to_hex(),
+                    self.role_cterm(*bg)
+                ));
+            }
+            line.push_str(&format!(" gui={} cterm={}\n", style, style));
+
+            output.push_str(&line);
+        }
+
+        let ansi: Vec<String> = self
+            .colors
+            .iter()
+            .map(|color| format!("'{}'", color.to_hex()))
+            .collect();
+        output.push_str(&format!(
+            "\nlet g:terminal_ansi_colors = [{}]\n",
+            ansi.join(", ")
+        ));
+
+        output
+    }
+
+    /// Helix theme
+    pub fn generate_helix_template(&self) -> String {
+        let mut output = String::new();
+        output.push_str("# Helix theme generated by walrus\n");
+        output.push_str(
+            "# Link to ~/.config/helix/themes/walrus.toml and set theme = \"walrus\"\n\n",
+        );
+
+        let scopes = [
+            ("ui.background", "{ bg = \"background\" }"),
+            ("ui.text", "\"foreground\""),
+            ("ui.text.focus", "{ fg = \"foreground\", bg = \"surface\" }"),
+            ("ui.cursor", "{ fg = \"background\", bg = \"foreground\" }"),
+            (
+                "ui.cursor.match",
+                "{ fg = \"color4\", modifiers = [\"bold\"] }",
+            ),
+            ("ui.cursorline.primary", "{ bg = \"surface\" }"),
+            ("ui.selection", "{ bg = \"selection\" }"),
+            ("ui.linenr", "\"comment\""),
+            ("ui.linenr.selected", "\"foreground\""),
+            ("ui.statusline", "{ fg = \"foreground\", bg = \"surface\" }"),
+            (
+                "ui.statusline.inactive",
+                "{ fg = \"comment\", bg = \"surface\" }",
+            ),
+            ("ui.popup", "{ fg = \"foreground\", bg = \"surface\" }"),
+            ("ui.window", "\"comment\""),
+            ("ui.help", "{ fg = \"foreground\", bg = \"surface\" }"),
+            ("ui.menu", "{ fg = \"foreground\", bg = \"surface\" }"),
+            (
+                "ui.menu.selected",
+                "{ fg = \"background\", bg = \"color4\" }",
+            ),
+            ("ui.virtual", "\"comment\""),
+            ("comment", "{ fg = \"comment\", modifiers = [\"italic\"] }"),
+            ("constant", "\"color3\""),
+            ("string", "\"color2\""),
+            ("variable", "\"foreground\""),
+            ("function", "\"color4\""),
+            ("keyword", "\"color5\""),
+            ("operator", "\"color6\""),
+            ("type", "\"color3\""),
+            ("namespace", "\"color6\""),
+            ("special", "\"color6\""),
+            ("punctuation", "\"foreground\""),
+            (
+                "markup.heading",
+                "{ fg = \"color4\", modifiers = [\"bold\"] }",
+            ),
+            (
+                "markup.link.url",
+                "{ fg = \"color4\", modifiers = [\"underlined\"] }",
+            ),
+            ("diff.plus", "\"color2\""),
+            ("diff.delta", "\"color3\""),
+            ("diff.minus", "\"color1\""),
+            ("error", "\"color1\""),
+            ("warning", "\"color3\""),
+            ("info", "\"color4\""),
+            ("hint", "\"color6\""),
+            (
+                "diagnostic.error",
+                "{ underline = { color = \"color1\", style = \"curl\" } }",
+            ),
+            (
+                "diagnostic.warning",
+                "{ underline = { color = \"color3\", style = \"curl\" } }",
+            ),
+        ];
+
+        for (scope, value) in scopes {
+            output.push_str(&format!("\"{}\" = {}\n", scope, value));
+        }
+
+        output.push_str("\n[palette]\n");
+        output.push_str(&format!(
+            "background = \"{}\"\n",
+            self.background().to_hex()
+        ));
+        output.push_str(&format!(
+            "foreground = \"{}\"\n",
+            self.foreground().to_hex()
+        ));
+        output.push_str(&format!("surface = \"{}\"\n", self.surface().to_hex()));
+        output.push_str(&format!("selection = \"{}\"\n", self.selection().to_hex()));
+        output.push_str(&format!("comment = \"{}\"\n", self.comment().to_hex()));
+        for (i, color) in self.colors.iter().enumerate() {
+            output.push_str(&format!("color{} = \"{}\"\n", i, color.to_hex()));
+        }
+
+        output
+    }
+
+    /// Manifest for a VS Code theme extension in the vscode/ output directory
+    pub fn generate_vscode_package_template(&self) -> String {
+        let ui_theme = if self.is_light() { "vs" } else { "vs-dark" };
+
+        let mut output = String::new();
+        output.push_str("{\n");
+        output.push_str("    \"name\": \"walrus-theme\",\n");
+        output.push_str("    \"displayName\": \"Walrus\",\n");
+        output.push_str("    \"description\": \"Color theme generated by walrus\",\n");
+        output.push_str("    \"version\": \"0.0.1\",\n");
+        output.push_str("    \"publisher\": \"walrus\",\n");
+        output.push_str("    \"engines\": { \"vscode\": \"^1.50.0\" },\n");
+        output.push_str("    \"categories\": [\"Themes\"],\n");
+        output.push_str("    \"contributes\": {\n");
+        output.push_str("        \"themes\": [\n");
+        output.push_str(&format!(
+            "            {{ \"label\": \"Walrus\", \"uiTheme\": \"{}\", \"path\": \"./themes/walrus-color-theme.json\" }}\n",
+            ui_theme
+        ));
+        output.push_str("        ]\n");
+        output.push_str("    }\n");
+        output.push_str("}\n");
+        output
+    }
+
+    /// VS Code color theme referenced by the vscode/package.json manifest
+    pub fn generate_vscode_theme_template(&self) -> String {
+        let background = self.background().to_hex();
+        let foreground = self.foreground().to_hex();
+        let surface = self.surface().to_hex();
+        let selection = self.selection().to_hex();
+        let comment = self.comment().to_hex();
+        let accent = self.accent().to_hex();
+
+        let ui_colors = vec![
+            ("editor.background", background.clone()),
+            ("editor.foreground", foreground.clone()),
+            ("editor.selectionBackground", selection.clone()),
+            ("editor.lineHighlightBackground", surface.clone()),
+            ("editorCursor.foreground", foreground.clone()),
+            ("editorLineNumber.foreground", comment.clone()),
+            ("editorLineNumber.activeForeground", foreground.clone()),
+            ("editorWidget.background", surface.clone()),
+            ("sideBar.background", surface.clone()),
+            ("sideBar.foreground", foreground.clone()),
+            ("activityBar.background", surface.clone()),
+            ("activityBar.foreground", foreground.clone()),
+            ("activityBarBadge.background", accent.clone()),
+            ("statusBar.background", surface.clone()),
+            ("statusBar.foreground", foreground.clone()),
+            ("titleBar.activeBackground", background.clone()),
+            ("titleBar.activeForeground", foreground.clone()),
+            ("tab.activeBackground", background.clone()),
+            ("tab.inactiveBackground", surface.clone()),
+            ("panel.background", background.clone()),
+            ("focusBorder", accent.clone()),
+            ("button.background", accent.clone()),
+            ("button.foreground", background.clone()),
+            ("terminal.background", background.clone()),
+            ("terminal.foreground", foreground.clone()),
+        ];
+
+        let mut terminal_names = Vec::new();
+        for (i, name) in ANSI_NAMES.iter().enumerate() {
+            let name = format!("{}{}", name[..1].to_uppercase(), &name[1..]);
+            terminal_names.push((format!("terminal.ansi{}", name), i));
+            terminal_names.push((format!("terminal.ansiBright{}", name), i + 8));
+        }
+
+        let token_colors = [
+            ("comment", comment.clone(), "italic"),
+            ("string", self.colors[2].to_hex(), ""),
+            (
+                "constant, constant.numeric, constant.language",
+                self.colors[3].to_hex(),
+                "",
+            ),
+            ("keyword, storage", self.colors[5].to_hex(), ""),
+            ("keyword.operator", self.colors[6].to_hex(), ""),
+            (
+                "entity.name.function, support.function",
+                self.colors[4].to_hex(),
+                "",
+            ),
+            (
+                "entity.name.type, support.type, storage.type",
+                self.colors[3].to_hex(),
+                "",
+            ),
+            ("variable", foreground.clone(), ""),
+            ("invalid", self.colors[1].to_hex(), ""),
+        ];
+
+        let mut output = String::new();
+        output.push_str("{\n");
+        output.push_str("    \"name\": \"Walrus\",\n");
+        output.push_str(&format!("    \"type\": \"{}\",\n", self.background_mode()));
+
+        output.push_str("    \"colors\": {\n");
+        let mut entries: Vec<(String, String)> = ui_colors
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        entries.extend(
+            terminal_names
+                .into_iter()
+                .map(|(key, i)| (key, self.colors[i].to_hex())),
+        );
+        for (i, (key, value)) in entries.iter().enumerate() {
+            let comma = if i == entries.len() - 1 { "" } else { "," };
+            output.push_str(&format!("        \"{}\": \"{}\"{}\n", key, value, comma));
+        }
+        output.push_str("    },\n");
+
+        output.push_str("    \"tokenColors\": [\n");
+        for (i, (scope, color, style)) in token_colors.iter().enumerate() {
+            let comma = if i == token_colors.len() - 1 { "" } else { "," };
+            let style = if style.is_empty() {
+                String::new()
+            } else {
+                format!(", \"fontStyle\": \"{}\"", style)
+            };
+            output.push_str(&format!(
+                "        {{ \"scope\": \"{}\", \"settings\": {{ \"foreground\": \"{}\"{} }} }}{}\n",
+                scope, color, style, comma
+            ));
+        }
+        output.push_str("    ]\n");
+        output.push_str("}\n");
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::template_gen;
+
+    #[test]
+    fn test_neovim_highlights() {
+        let output = template_gen().generate_neovim_template();
+        assert!(output.contains("vim.g.colors_name = \"walrus\"\n"));
+        assert!(output.contains("hl(0, \"Normal\", { fg = \"#f0000f\", bg = \"#0000ff\" })\n"));
+        assert!(output.contains("hl(0, \"Comment\", { fg = \"#8000"));
+        assert!(output.contains("vim.g.terminal_color_15 = \"#f0000f\"\n"));
+    }
+
+    #[test]
+    fn test_vim_highlights() {
+        let output = template_gen().generate_vim_template();
+        assert!(output.contains("let g:colors_name = \"walrus\"\n"));
+        assert!(output.contains(
+            "hi Normal guifg=#f0000f ctermfg=15 guibg=#0000ff ctermbg=0 gui=NONE cterm=NONE\n"
+        ));
+        assert!(output.contains("let g:terminal_ansi_colors = ['#0000ff', "));
+    }
+
+    #[test]
+    fn test_helix_palette() {
+        let output = template_gen().generate_helix_template();
+        assert!(output.contains("\"ui.background\" = { bg = \"background\" }\n"));
+        assert!(output.contains("[palette]\nbackground = \"#0000ff\"\n"));
+        assert!(output.contains("color15 = \"#f0000f\"\n"));
+    }
+
+    #[test]
+    fn test_vscode_theme() {
+        let template_gen = template_gen();
+        let package = template_gen.generate_vscode_package_template();
+        assert!(package.contains("\"path\": \"./themes/walrus-color-theme.json\""));
+
+        let theme = template_gen.generate_vscode_theme_template();
+        assert!(theme.contains("\"editor.background\": \"#0000ff\","));
+        assert!(theme.contains("\"terminal.ansiBrightWhite\": \"#f0000f\"\n"));
+        let accent = template_gen.accent().to_hex();
+        assert!(theme.contains(&format!("\"focusBorder\": \"{}\",", accent)));
+        assert!(theme.contains("\"tokenColors\": ["));
+    }
+}
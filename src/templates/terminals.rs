@@ -0,0 +1,341 @@
+use super::{ANSI_NAMES, TemplateGenerator};
+
+impl TemplateGenerator {
+    /// kitty colors to include from kitty.conf
+    pub fn generate_kitty_template(&self) -> String {
+        let background = self.background().to_hex();
+        let foreground = self.foreground().to_hex();
+
+        let mut output = String::new();
+        output.push_str("# Kitty colors generated by walrus\n");
+        output.push_str("# Use with: include colors-kitty.conf\n\n");
+
+        output.push_str(&format!("foreground {}\n", foreground));
+        output.push_str(&format!("background {}\n", background));
+        output.push_str(&format!("cursor {}\n", foreground));
+        output.push_str(&format!("cursor_text_color {}\n", background));
+        output.push_str(&format!("selection_foreground {}\n", foreground));
+        output.push_str(&format!(
+            "selection_background {}\n",
+            self.selection().to_hex()
+        ));
+        output.push_str(&format!("url_color {}\n", self.colors[4].to_hex()));
+        output.push_str(&format!("active_border_color {}\n", foreground));
+        output.push_str(&format!(
+            "inactive_border_color {}\n\n",
+            self.selection().to_hex()
+        ));
+
+        for (i, color) in self.colors.iter().enumerate() {
+            output.push_str(&format!("color{} {}\n", i, color.to_hex()));
+        }
+
+        output
+    }
+
+    /// Alacritty colors to import from alacritty.toml
+    pub fn generate_alacritty_template(&self) -> String {
+        let mut output = String::new();
+        output.push_str("# Alacritty colors generated by walrus\n");
+        output.push_str("# Use with: import = [\"~/.cache/walrus/colors-alacritty.toml\"]\n\n");
+
+        output.push_str("[colors.primary]\n");
+        output.push_str(&format!(
+            "background = \"{}\"\n",
+            self.background().to_hex()
+        ));
+        output.push_str(&format!(
+            "foreground = \"{}\"\n\n",
+            self.foreground().to_hex()
+        ));
+
+        output.push_str("[colors.cursor]\n");
+        output.push_str(&format!("text = \"{}\"\n", self.background().to_hex()));
+        output.push_str(&format!("cursor = \"{}\"\n\n", self.foreground().to_hex()));
+
+        output.push_str("[colors.selection]\n");
+        output.push_str(&format!("text = \"{}\"\n", self.foreground().to_hex()));
+        output.push_str(&format!("background = \"{}\"\n", self.selection().to_hex()));
+
+        for (section, offset) in [("normal", 0), ("bright", 8)] {
+            output.push_str(&format!("\n[colors.{}]\n", section));
+            for (i, name) in ANSI_NAMES.iter().enumerate() {
+                output.push_str(&format!(
+                    "{} = \"{}\"\n",
+                    name,
+                    self.colors[offset + i].to_hex()
+                ));
+            }
+        }
+
+        output
+    }
+
+    /// foot colors to include from foot.ini
+    pub fn generate_foot_template(&self) -> String {
+        let mut output = String::new();
+        output.push_str("# Foot colors generated by walrus\n");
+        output.push_str("# Use with: include=~/.cache/walrus/colors-foot.ini\n\n");
+
+        output.push_str("[cursor]\n");
+        output.push_str(&format!(
+            "color={} {}\n\n",
+            self.background().to_hex_stripped(),
+            self.foreground().to_hex_stripped()
+        ));
+
+        output.push_str("[colors]\n");
+        output.push_str(&format!(
+            "foreground={}\n",
+            self.foreground().to_hex_stripped()
+        ));
+        output.push_str(&format!(
+            "background={}\n",
+            self.background().to_hex_stripped()
+        ));
+        output.push_str(&format!(
+            "selection-foreground={}\n",
+            self.foreground().to_hex_stripped()
+        ));
+        output.push_str(&format!(
+            "selection-background={}\n",
+            self.selection().to_hex_stripped()
+        ));
+
+        for i in 0..8 {
+            output.push_str(&format!(
+                "regular{}={}\n",
+                i,
+                self.colors[i].to_hex_stripped()
+            ));
+        }
+        for i in 0..8 {
+            output.push_str(&format!(
+                "bright{}={}\n",
+                i,
+                self.colors[i + 8].to_hex_stripped()
+            ));
+        }
+
+        output
+    }
+
+    /// WezTerm color scheme for ~/.config/wezterm/colors
+    pub fn generate_wezterm_template(&self) -> String {
+        let quoted = |range: std::ops::Range<usize>| {
+            self.colors[range]
+                .iter()
+                .map(|color| format!("\"{}\"", color.to_hex()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut output = String::new();
+        output.push_str("# WezTerm color scheme generated by walrus\n");
+        output
+            .push_str("# Copy to ~/.config/wezterm/colors/ and set color_scheme = \"Walrus\"\n\n");
+
+        output.push_str("[colors]\n");
+        output.push_str(&format!(
+            "foreground = \"{}\"\n",
+            self.foreground().to_hex()
+        ));
+        output.push_str(&format!(
+            "background = \"{}\"\n",
+            self.background().to_hex()
+        ));
+        output.push_str(&format!("cursor_bg = \"{}\"\n", self.foreground().to_hex()));
+        output.push_str(&format!("cursor_fg = \"{}\"\n", self.background().to_hex()));
+        output.push_str(&format!(
+            "cursor_border = \"{}\"\n",
+            self.foreground().to_hex()
+        ));
+        output.push_str(&format!(
+            "selection_fg = \"{}\"\n",
+            self.foreground().to_hex()
+        ));
+        output.push_str(&format!(
+            "selection_bg = \"{}\"\n",
+            self.selection().to_hex()
+        ));
+        output.push_str(&format!("ansi = [{}]\n", quoted(0..8)));
+        output.push_str(&format!("brights = [{}]\n\n", quoted(8..16)));
+
+        output.push_str("[metadata]\n");
+        output.push_str("name = \"Walrus\"\n");
+
+        output
+    }
+
+    /// Ghostty theme
+    pub fn generate_ghostty_template(&self) -> String {
+        let mut output = String::new();
+        output.push_str("# Ghostty theme generated by walrus\n");
+        output.push_str("# Use with: config-file = ~/.cache/walrus/colors-ghostty\n\n");
+
+        output.push_str(&format!("background = {}\n", self.background().to_hex()));
+        output.push_str(&format!("foreground = {}\n", self.foreground().to_hex()));
+        output.push_str(&format!("cursor-color = {}\n", self.foreground().to_hex()));
+        output.push_str(&format!("cursor-text = {}\n", self.background().to_hex()));
+        output.push_str(&format!(
+            "selection-background = {}\n",
+            self.selection().to_hex()
+        ));
+        output.push_str(&format!(
+            "selection-foreground = {}\n",
+            self.foreground().to_hex()
+        ));
+
+        for (i, color) in self.colors.iter().enumerate() {
+            output.push_str(&format!("palette = {}={}\n", i, color.to_hex()));
+        }
+
+        output
+    }
+
+    /// Konsole color scheme
+    pub fn generate_konsole_template(&self) -> String {
+        let rgb = |color: &crate::color::Color| format!("{},{},{}", color.r, color.g, color.b);
+
+        let mut output = String::new();
+        let mut section = |name: &str, normal, intense| {
+            let faint = self.background().mix(normal, 0.6);
+            output.push_str(&format!("[{}]\nColor={}\n\n", name, rgb(normal)));
+            output.push_str(&format!("[{}Intense]\nColor={}\n\n", name, rgb(intense)));
+            output.push_str(&format!("[{}Faint]\nColor={}\n\n", name, rgb(&faint)));
+        };
+
+        section("Background", self.background(), self.background());
+        section("Foreground", self.foreground(), self.foreground());
+        for i in 0..8 {
+            section(&format!("Color{}", i), &self.colors[i], &self.colors[i + 8]);
+        }
+
+        output.push_str("[General]\n");
+        output.push_str("Description=Walrus\n");
+        output.push_str("Opacity=1\n");
+        output.push_str("Wallpaper=\n");
+
+        output
+    }
+
+    /// Windows Terminal scheme to paste into settings.json
+    pub fn generate_windows_terminal_template(&self) -> String {
+        let mut entries = vec![
+            ("name".to_string(), "Walrus".to_string()),
+            ("background".to_string(), self.background().to_hex()),
+            ("foreground".to_string(), self.foreground().to_hex()),
+            ("cursorColor".to_string(), self.foreground().to_hex()),
+            ("selectionBackground".to_string(), self.selection().to_hex()),
+        ];
+
+        // Windows Terminal calls magenta "purple"
+        for (i, name) in ANSI_NAMES.iter().enumerate() {
+            let name = if *name == "magenta" { "purple" } else { name };
+            entries.push((name.to_string(), self.colors[i].to_hex()));
+        }
+        for (i, name) in ANSI_NAMES.iter().enumerate() {
+            let name = if *name == "magenta" { "purple" } else { name };
+            let bright = format!("bright{}{}", name[..1].to_uppercase(), &name[1..]);
+            entries.push((bright, self.colors[i + 8].to_hex()));
+        }
+
+        let mut output = String::new();
+        output.push_str("{\n");
+        for (i, (key, value)) in entries.iter().enumerate() {
+            let comma = if i == entries.len() - 1 { "" } else { "," };
+            output.push_str(&format!("    \"{}\": \"{}\"{}\n", key, value, comma));
+        }
+        output.push_str("}\n");
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::template_gen;
+
+    #[test]
+    fn test_kitty_keys() {
+        let output = template_gen().generate_kitty_template();
+        for key in ["foreground", "background", "cursor", "selection_background"] {
+            assert!(
+                output
+                    .lines()
+                    .any(|line| line.starts_with(&format!("{} #", key)))
+            );
+        }
+        assert!(output.contains("color0 #0000ff\n"));
+        assert!(output.contains("color15 #f0000f\n"));
+    }
+
+    #[test]
+    fn test_alacritty_keys() {
+        let output = template_gen().generate_alacritty_template();
+        for section in ["primary", "cursor", "selection", "normal", "bright"] {
+            assert!(output.contains(&format!("[colors.{}]\n", section)));
+        }
+        assert!(output.contains("black = \"#0000ff\"\n"));
+        assert!(output.contains("white = \"#f0000f\"\n"));
+    }
+
+    #[test]
+    fn test_foot_keys() {
+        let output = template_gen().generate_foot_template();
+        assert!(output.contains("[colors]\n"));
+        assert!(output.contains("background=0000ff\n"));
+        assert!(output.contains("regular0=0000ff\n"));
+        assert!(output.contains("bright7=f0000f\n"));
+        assert!(output.contains("[cursor]\ncolor=0000ff f0000f\n"));
+    }
+
+    #[test]
+    fn test_wezterm_keys() {
+        let output = template_gen().generate_wezterm_template();
+        assert!(output.contains("[colors]\n"));
+        assert!(output.contains("ansi = [\"#0000ff\", "));
+        assert!(output.contains("brights = [\"#8000"));
+        assert!(output.contains("cursor_bg = \"#f0000f\"\n"));
+    }
+
+    #[test]
+    fn test_ghostty_keys() {
+        let output = template_gen().generate_ghostty_template();
+        assert!(output.contains("background = #0000ff\n"));
+        assert!(output.contains("palette = 0=#0000ff\n"));
+        assert!(output.contains("palette = 15=#f0000f\n"));
+        assert!(output.contains("cursor-color = #f0000f\n"));
+    }
+
+    #[test]
+    fn test_konsole_keys() {
+        let output = template_gen().generate_konsole_template();
+        for section in ["Background", "Foreground", "Color0", "Color7"] {
+            assert!(output.contains(&format!("[{}]\nColor=", section)));
+            assert!(output.contains(&format!("[{}Intense]\nColor=", section)));
+            assert!(output.contains(&format!("[{}Faint]\nColor=", section)));
+        }
+        assert!(output.contains("[Background]\nColor=0,0,255\n"));
+        assert!(output.contains("[General]\nDescription=Walrus\n"));
+    }
+
+    #[test]
+    fn test_windows_terminal_keys() {
+        let output = template_gen().generate_windows_terminal_template();
+        for key in [
+            "name",
+            "background",
+            "foreground",
+            "cursorColor",
+            "selectionBackground",
+            "black",
+            "purple",
+            "brightBlack",
+            "brightPurple",
+            "brightWhite",
+        ] {
+            assert!(output.contains(&format!("\"{}\": ", key)), "{}", key);
+        }
+        assert!(output.contains("\"brightWhite\": \"#f0000f\"\n}"));
+    }
+}
@@ -0,0 +1,251 @@
+use super::TemplateGenerator;
+use crate::color::Color;
+
+// Number of QPalette roles in a qt5ct/qt6ct color scheme line
+const QT_PALETTE_ROLES: usize = 21;
+
+fn kde_rgb(color: &Color) -> String {
+    format!("{},{},{}", color.r, color.g, color.b)
+}
+
+impl TemplateGenerator {
+    /// GTK3 and libadwaita (GTK4) named colors
+    pub fn generate_gtk_template(&self) -> String {
+        let background = *self.background();
+        let foreground = *self.foreground();
+        let surface = self.surface();
+        let accent = *self.accent();
+        let shade = "rgba(0, 0, 0, 0.07)".to_string();
+
+        let colors: Vec<(&str, String)> = vec![
+            ("accent_color", accent.to_hex()),
+            ("accent_bg_color", accent.to_hex()),
+            ("accent_fg_color", self.text_on(&accent).to_hex()),
+            ("destructive_color", self.urgent().to_hex()),
+            ("destructive_bg_color", self.urgent().to_hex()),
+            ("destructive_fg_color", self.text_on(self.urgent()).to_hex()),
+            ("success_color", self.colors[2].to_hex()),
+            ("success_bg_color", self.colors[2].to_hex()),
+            ("success_fg_color", self.text_on(&self.colors[2]).to_hex()),
+            ("warning_color", self.colors[3].to_hex()),
+            ("warning_bg_color", self.colors[3].to_hex()),
+            ("warning_fg_color", self.text_on(&self.colors[3]).to_hex()),
+            ("error_color", self.urgent().to_hex()),
+            ("error_bg_color", self.urgent().to_hex()),
+            ("error_fg_color", self.text_on(self.urgent()).to_hex()),
+            ("window_bg_color", background.to_hex()),
+            ("window_fg_color", foreground.to_hex()),
+            ("view_bg_color", background.to_hex()),
+            ("view_fg_color", foreground.to_hex()),
+            ("headerbar_bg_color", surface.to_hex()),
+            ("headerbar_fg_color", foreground.to_hex()),
+            ("headerbar_border_color", foreground.to_hex()),
+            ("headerbar_backdrop_color", background.to_hex()),
+            ("headerbar_shade_color", shade.clone()),
+            ("sidebar_bg_color", surface.to_hex()),
+            ("sidebar_fg_color", foreground.to_hex()),
+            ("sidebar_backdrop_color", background.to_hex()),
+            ("sidebar_shade_color", shade.clone()),
+            ("card_bg_color", surface.to_hex()),
+            ("card_fg_color", foreground.to_hex()),
+            ("card_shade_color", shade.clone()),
+            ("dialog_bg_color", surface.to_hex()),
+            ("dialog_fg_color", foreground.to_hex()),
+            ("popover_bg_color", surface.to_hex()),
+            ("popover_fg_color", foreground.to_hex()),
+            ("shade_color", shade),
+            ("scrollbar_outline_color", background.to_hex()),
+            // GTK3 (Adwaita) names
+            ("theme_bg_color", background.to_hex()),
+            ("theme_fg_color", foreground.to_hex()),
+            ("theme_base_color", background.to_hex()),
+            ("theme_text_color", foreground.to_hex()),
+            ("theme_selected_bg_color", accent.to_hex()),
+            ("theme_selected_fg_color", self.text_on(&accent).to_hex()),
+            ("insensitive_bg_color", surface.to_hex()),
+            ("insensitive_fg_color", self.comment().to_hex()),
+            ("borders", self.selection().to_hex()),
+        ];
+
+        let mut output = String::new();
+        output.push_str("/* GTK3/GTK4 colors generated by walrus */\n");
+        output.push_str(
+            "/* Use with: @import url(\"file:///path/to/colors-gtk.css\"); in gtk-3.0/gtk.css or gtk-4.0/gtk.css */\n\n",
+        );
+
+        for (name, value) in colors {
+            output.push_str(&format!("@define-color {} {};\n", name, value));
+        }
+
+        output
+    }
+
+    /// qt5ct/qt6ct color scheme, colors are #aarrggbb
+    pub fn generate_qt_template(&self) -> String {
+        let argb = |color: &Color| format!("#ff{}", color.to_hex_stripped());
+
+        let palette = |foreground: Color, background: Color| {
+            let surface = background.mix(&foreground, 0.08);
+            // In QPalette::ColorRole order
+            let roles: [Color; QT_PALETTE_ROLES] = [
+                foreground,                        // WindowText
+                surface,                           // Button
+                background.mix(&foreground, 0.2),  // Light
+                background.mix(&foreground, 0.14), // Midlight
+                background.darken(0.4),            // Dark
+                background.mix(&foreground, 0.3),  // Mid
+                foreground,                        // Text
+                *self.foreground(),                // BrightText
+                foreground,                        // ButtonText
+                background,                        // Base
+                background,                        // Window
+                background.darken(0.7),            // Shadow
+                *self.accent(),                    // Highlight
+                *self.text_on(self.accent()),      // HighlightedText
+                self.colors[4],                    // Link
+                self.colors[5],                    // LinkVisited
+                surface,                           // AlternateBase
+                background,                        // NoRole
+                surface,                           // ToolTipBase
+                foreground,                        // ToolTipText
+                *self.comment(),                   // PlaceholderText
+            ];
+            roles.iter().map(argb).collect::<Vec<_>>().join(", ")
+        };
+
+        let disabled_foreground = self.background().mix(self.foreground(), 0.5);
+
+        let mut output = String::new();
+        output.push_str("[ColorScheme]\n");
+        output.push_str(&format!(
+            "active_colors={}\n",
+            palette(*self.foreground(), *self.background())
+        ));
+        output.push_str(&format!(
+            "disabled_colors={}\n",
+            palette(disabled_foreground, *self.background())
+        ));
+        output.push_str(&format!(
+            "inactive_colors={}\n",
+            palette(*self.foreground(), *self.background())
+        ));
+
+        output
+    }
+
+    /// KDE Plasma color scheme, copy to ~/.local/share/color-schemes/Walrus.colors
+    pub fn generate_kde_template(&self) -> String {
+        let background = *self.background();
+        let foreground = *self.foreground();
+        let surface = self.surface();
+        let accent = *self.accent();
+
+        let mut output = String::new();
+        output.push_str("# KDE color scheme generated by walrus\n\n");
+
+        let sections = [
+            ("View", background, foreground),
+            ("Window", background, foreground),
+            ("Button", surface, foreground),
+            ("Header", surface, foreground),
+            ("Tooltip", surface, foreground),
+            ("Complementary", surface, foreground),
+            ("Selection", accent, *self.text_on(&accent)),
+        ];
+
+        for (name, section_background, section_foreground) in sections {
+            output.push_str(&format!("[Colors:{}]\n", name));
+            output.push_str(&format!(
+                "BackgroundAlternate={}\n",
+                kde_rgb(&section_background.mix(&section_foreground, 0.05))
+            ));
+            output.push_str(&format!(
+                "BackgroundNormal={}\n",
+                kde_rgb(&section_background)
+            ));
+            output.push_str(&format!("DecorationFocus={}\n", kde_rgb(&accent)));
+            output.push_str(&format!("DecorationHover={}\n", kde_rgb(&accent)));
+            output.push_str(&format!("ForegroundActive={}\n", kde_rgb(&self.colors[4])));
+            output.push_str(&format!(
+                "ForegroundInactive={}\n",
+                kde_rgb(&section_background.mix(&section_foreground, 0.6))
+            ));
+            output.push_str(&format!("ForegroundLink={}\n", kde_rgb(&self.colors[4])));
+            output.push_str(&format!("ForegroundNegative={}\n", kde_rgb(self.urgent())));
+            output.push_str(&format!("ForegroundNeutral={}\n", kde_rgb(&self.colors[3])));
+            output.push_str(&format!(
+                "ForegroundNormal={}\n",
+                kde_rgb(&section_foreground)
+            ));
+            output.push_str(&format!(
+                "ForegroundPositive={}\n",
+                kde_rgb(&self.colors[2])
+            ));
+            output.push_str(&format!(
+                "ForegroundVisited={}\n\n",
+                kde_rgb(&self.colors[5])
+            ));
+        }
+
+        output.push_str("[General]\n");
+        output.push_str("ColorScheme=Walrus\n");
+        output.push_str("Name=Walrus\n\n");
+
+        output.push_str("[WM]\n");
+        output.push_str(&format!("activeBackground={}\n", kde_rgb(&surface)));
+        output.push_str(&format!("activeForeground={}\n", kde_rgb(&foreground)));
+        output.push_str(&format!("inactiveBackground={}\n", kde_rgb(&background)));
+        output.push_str(&format!("inactiveForeground={}\n", kde_rgb(self.comment())));
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::template_gen;
+    use super::*;
+
+    #[test]
+    fn test_gtk_named_colors() {
+        let output = template_gen().generate_gtk_template();
+        for name in [
+            "accent_bg_color",
+            "window_bg_color",
+            "window_fg_color",
+            "view_bg_color",
+            "headerbar_bg_color",
+            "theme_bg_color",
+        ] {
+            assert!(
+                output.contains(&format!("@define-color {} ", name)),
+                "{}",
+                name
+            );
+        }
+        assert!(output.contains("@define-color window_bg_color #0000ff;\n"));
+    }
+
+    #[test]
+    fn test_qt_color_scheme() {
+        let output = template_gen().generate_qt_template();
+        for key in ["active_colors", "disabled_colors", "inactive_colors"] {
+            let line = output
+                .lines()
+                .find(|line| line.starts_with(&format!("{}=", key)))
+                .unwrap();
+            assert_eq!(line.split(", ").count(), QT_PALETTE_ROLES);
+        }
+        assert!(output.starts_with("[ColorScheme]\nactive_colors=#fff0000f, "));
+    }
+
+    #[test]
+    fn test_kde_color_scheme() {
+        let output = template_gen().generate_kde_template();
+        for section in ["View", "Window", "Button", "Selection", "Tooltip"] {
+            assert!(output.contains(&format!("[Colors:{}]\n", section)));
+        }
+        assert!(output.contains("BackgroundNormal=0,0,255\n"));
+        assert!(output.contains("[General]\nColorScheme=Walrus\n"));
+    }
+}
@@ -0,0 +1,241 @@
+//! Predefined themes: bundled base16 schemes and user JSON/YAML theme files
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::color::Color;
+use crate::error::{Result, WalrusError};
+
+// Themes shipped inside the binary, selectable with --theme NAME
+const BUNDLED_THEMES: &[(&str, &str)] = &[
+    (
+        "catppuccin-mocha",
+        include_str!("../themes/catppuccin-mocha.yaml"),
+    ),
+    ("dracula", include_str!("../themes/dracula.yaml")),
+    ("gruvbox-dark", include_str!("../themes/gruvbox-dark.yaml")),
+    (
+        "gruvbox-light",
+        include_str!("../themes/gruvbox-light.yaml"),
+    ),
+    ("nord", include_str!("../themes/nord.yaml")),
+    ("one-dark", include_str!("../themes/one-dark.yaml")),
+    ("rose-pine", include_str!("../themes/rose-pine.yaml")),
+    (
+        "solarized-dark",
+        include_str!("../themes/solarized-dark.yaml"),
+    ),
+    (
+        "solarized-light",
+        include_str!("../themes/solarized-light.yaml"),
+    ),
+    ("tokyo-night", include_str!("../themes/tokyo-night.yaml")),
+];
+
+// Terminal color index -> base16 role (same mapping as base16-shell)
+const BASE16_TERMINAL_MAP: [&str; 16] = [
+    "base00", "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base05", "base03",
+    "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base07",
+];
+
+// Base24 has dedicated bright accents in base12-base17
+const BASE24_TERMINAL_MAP: [&str; 16] = [
+    "base00", "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base05", "base03",
+    "base12", "base14", "base13", "base16", "base17", "base15", "base07",
+];
+
+/// Names of the themes bundled with walrus
+pub fn bundled_theme_names() -> Vec<&'static str> {
+    BUNDLED_THEMES.iter().map(|(name, _)| *name).collect()
+}
+
+fn user_themes_dir() -> PathBuf {
+    let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    home_dir.join(".config/walrus/themes")
+}
+
+/// Resolve --theme: a file path, a theme in ~/.config/walrus/themes, or a bundled theme
+pub fn load_theme(name: &str) -> Result<Vec<Color>> {
+    match find_theme_file(name) {
+        Some(path) => load_theme_file(&path),
+        None => parse_theme(bundled_theme(name)?),
+    }
+}
+
+/// The base00-base0F roles of a base16 --theme (base00-base17 for base24),
+/// None for JSON themes which only have the 16 terminal colors
+pub fn load_base_scheme(name: &str) -> Result<Option<Vec<Color>>> {
+    let content = match find_theme_file(name) {
+        Some(path) => fs::read_to_string(&path).map_err(|e| WalrusError::io(&path, e))?,
+        None => bundled_theme(name)?.to_string(),
+    };
+
+    Ok(base_scheme(&content))
+}
+
+fn find_theme_file(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+
+    let user_dir = user_themes_dir();
+    ["yaml", "yml", "json"]
+        .iter()
+        .map(|extension| user_dir.join(format!("{}.{}", name, extension)))
+        .find(|candidate| candidate.is_file())
+}
+
+fn bundled_theme(name: &str) -> Result<&'static str> {
+    let (_, content) = BUNDLED_THEMES
+        .iter()
+        .find(|(bundled, _)| *bundled == name)
+        .ok_or_else(|| {
+            format!(
+                "Unknown theme: {}. Available themes: {}",
+                name,
+                bundled_theme_names().join(", ")
+            )
+        })?;
+
+    Ok(content)
+}
+
+/// Load a JSON or base16/base24 YAML theme file
+pub fn load_theme_file(path: &Path) -> Result<Vec<Color>> {
+    let content = fs::read_to_string(path).map_err(|e| WalrusError::io(path, e))?;
+    parse_theme(&content).map_err(|e| WalrusError::Config(format!("{}: {}", path.display(), e)))
+}
+
+/// Detect the format from the content: pywal/walrus JSON or base16/base24 YAML
+pub fn parse_theme(content: &str) -> Result<Vec<Color>> {
+    if content.trim_start().starts_with('{') {
+        parse_json_theme(content)
+    } else {
+        parse_base16_theme(content)
+    }
+}
+
+// pywal colors.json uses "#rrggbb", walrus colors.json uses "rrggbb"
+fn parse_json_theme(content: &str) -> Result<Vec<Color>> {
+    let re = Regex::new(r#""color(\d{1,2})"\s*:\s*"(#?[0-9a-fA-F]{6})""#).unwrap();
+
+    let mut colors: [Option<Color>; 16] = [None; 16];
+    for caps in re.captures_iter(content) {
+        // At most two digits, always parses
+        let index: usize = caps[1].parse().unwrap();
+        if index < 16 {
+            colors[index] = Color::from_hex(&caps[2]);
+        }
+    }
+
+    colors
+        .iter()
+        .enumerate()
+        .map(|(i, color)| color.ok_or_else(|| format!("Missing color{} in theme", i).into()))
+        .collect()
+}
+
+fn parse_base16_theme(content: &str) -> Result<Vec<Color>> {
+    let roles = base16_roles(content);
+
+    let map = if (0x10..=0x17).all(|i| roles.contains_key(&format!("base{:02X}", i))) {
+        &BASE24_TERMINAL_MAP
+    } else {
+        &BASE16_TERMINAL_MAP
+    };
+
+    map.iter()
+        .map(|role| {
+            roles
+                .get(*role)
+                .copied()
+                .ok_or_else(|| format!("Missing {} in theme", role).into())
+        })
+        .collect()
+}
+
+// Colors by role name, normalised to base0A style
+fn base16_roles(content: &str) -> HashMap<String, Color> {
+    let re = Regex::new(r#"(?m)^\s*(base[0-9a-fA-F]{2})\s*:\s*["']?(#?[0-9a-fA-F]{6})"#).unwrap();
+
+    let mut roles: HashMap<String, Color> = HashMap::new();
+    for caps in re.captures_iter(content) {
+        if let Some(color) = Color::from_hex(&caps[2]) {
+            let role = format!("base{}", caps[1][4..].to_uppercase());
+            roles.insert(role, color);
+        }
+    }
+
+    roles
+}
+
+fn base_scheme(content: &str) -> Option<Vec<Color>> {
+    if content.trim_start().starts_with('{') {
+        return None;
+    }
+
+    let roles = base16_roles(content);
+    let scheme = |count: usize| -> Option<Vec<Color>> {
+        (0..count)
+            .map(|i| roles.get(&format!("base{:02X}", i)).copied())
+            .collect()
+    };
+    scheme(24).or_else(|| scheme(16))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_themes_parse() {
+        for name in bundled_theme_names() {
+            let colors = load_theme(name).unwrap();
+            assert_eq!(colors.len(), 16, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_parse_pywal_json() {
+        let mut content =
+            String::from("{\n  \"special\": {\"background\": \"#101010\"},\n  \"colors\": {\n");
+        for i in 0..16 {
+            content.push_str(&format!("    \"color{}\": \"#{:02x}0000\",\n", i, i * 16));
+        }
+        content.push_str("  }\n}\n");
+
+        let colors = parse_theme(&content).unwrap();
+        assert_eq!(colors[0], Color::new(0, 0, 0));
+        assert_eq!(colors[15], Color::new(240, 0, 0));
+    }
+
+    #[test]
+    fn test_parse_base16_yaml() {
+        let colors = parse_theme(include_str!("../themes/gruvbox-dark.yaml")).unwrap();
+        assert_eq!(colors[0], Color::from_hex("282828").unwrap());
+        assert_eq!(colors[1], Color::from_hex("fb4934").unwrap());
+        assert_eq!(colors[15], Color::from_hex("fbf1c7").unwrap());
+    }
+
+    #[test]
+    fn test_base_scheme_keeps_roles() {
+        let scheme = base_scheme(include_str!("../themes/gruvbox-dark.yaml")).unwrap();
+        assert_eq!(scheme.len(), 16);
+        assert_eq!(scheme[0x00], Color::from_hex("282828").unwrap());
+        assert_eq!(scheme[0x09], Color::from_hex("fe8019").unwrap());
+        assert_eq!(scheme[0x0F], Color::from_hex("d65d0e").unwrap());
+
+        let json = "{ \"colors\": { \"color0\": \"000000\" } }";
+        assert_eq!(base_scheme(json), None);
+        assert!(load_base_scheme("dracula").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_missing_colors_rejected() {
+        assert!(parse_theme("{ \"colors\": { \"color0\": \"000000\" } }").is_err());
+        assert!(parse_theme("base00: \"000000\"").is_err());
+    }
+}
@@ -0,0 +1,241 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::error::{Result, WalrusError};
+
+/// Wallpaper setters walrus knows how to drive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WallpaperSetter {
+    Swww,
+    Swaybg,
+    Hyprpaper,
+    Feh,
+    Xwallpaper,
+    Gsettings,
+    // Shell command template, {image} is replaced with the wallpaper path
+    Custom(String),
+}
+
+impl WallpaperSetter {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "swww" => Some(WallpaperSetter::Swww),
+            "swaybg" => Some(WallpaperSetter::Swaybg),
+            "hyprpaper" => Some(WallpaperSetter::Hyprpaper),
+            "feh" => Some(WallpaperSetter::Feh),
+            "xwallpaper" => Some(WallpaperSetter::Xwallpaper),
+            "gsettings" | "gnome" => Some(WallpaperSetter::Gsettings),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            WallpaperSetter::Swww => "swww",
+            WallpaperSetter::Swaybg => "swaybg",
+            WallpaperSetter::Hyprpaper => "hyprpaper",
+            WallpaperSetter::Feh => "feh",
+            WallpaperSetter::Xwallpaper => "xwallpaper",
+            WallpaperSetter::Gsettings => "gsettings",
+            WallpaperSetter::Custom(_) => "custom",
+        }
+    }
+
+    /// Pick a setter from $XDG_CURRENT_DESKTOP / $WAYLAND_DISPLAY and what is installed
+    pub fn detect() -> Option<Self> {
+        let desktop = env::var("XDG_CURRENT_DESKTOP")
+            .unwrap_or_default()
+            .to_lowercase();
+        let wayland = env::var_os("WAYLAND_DISPLAY").is_some();
+
+        let candidates: Vec<WallpaperSetter> = if ["gnome", "unity", "budgie"]
+            .iter()
+            .any(|de| desktop.contains(de))
+        {
+            vec![WallpaperSetter::Gsettings]
+        } else if wayland {
+            let mut setters = vec![WallpaperSetter::Swww];
+            if desktop.contains("hyprland") {
+                setters.push(WallpaperSetter::Hyprpaper);
+            }
+            setters.push(WallpaperSetter::Swaybg);
+            setters
+        } else {
+            vec![WallpaperSetter::Feh, WallpaperSetter::Xwallpaper]
+        };
+
+        candidates.into_iter().find(|setter| {
+            let program = match setter {
+                WallpaperSetter::Hyprpaper => "hyprctl",
+                other => other.name(),
+            };
+            find_in_path(program).is_some()
+        })
+    }
+
+    fn commands(&self, image_path: &str) -> Vec<Vec<String>> {
+        let image = image_path.to_string();
+        match self {
+            WallpaperSetter::Swww => vec![vec!["swww".into(), "img".into(), image]],
+            WallpaperSetter::Swaybg => {
+                vec![vec![
+                    "swaybg".into(),
+                    "-m".into(),
+                    "fill".into(),
+                    "-i".into(),
+                    image,
+                ]]
+            }
+            WallpaperSetter::Hyprpaper => vec![
+                vec![
+                    "hyprctl".into(),
+                    "hyprpaper".into(),
+                    "preload".into(),
+                    image.clone(),
+                ],
+                vec![
+                    "hyprctl".into(),
+                    "hyprpaper".into(),
+                    "wallpaper".into(),
+                    format!(",{}", image),
+                ],
+            ],
+            WallpaperSetter::Feh => vec![vec!["feh".into(), "--bg-fill".into(), image]],
+            WallpaperSetter::Xwallpaper => {
+                vec![vec!["xwallpaper".into(), "--zoom".into(), image]]
+            }
+            WallpaperSetter::Gsettings => {
+                let uri = file_uri(&image);
+                ["picture-uri", "picture-uri-dark"]
+                    .iter()
+                    .map(|key| {
+                        vec![
+                            "gsettings".into(),
+                            "set".into(),
+                            "org.gnome.desktop.background".into(),
+                            key.to_string(),
+                            uri.clone(),
+                        ]
+                    })
+                    .collect()
+            }
+            WallpaperSetter::Custom(template) => vec![vec![
+                "sh".into(),
+                "-c".into(),
+                expand_command_template(template, image_path),
+            ]],
+        }
+    }
+
+    pub fn set_wallpaper(&self, image_path: &str) -> Result<()> {
+        let image_path = absolute_path(Path::new(image_path));
+        let image_path = image_path.to_string_lossy();
+
+        // swaybg keeps running to draw the wallpaper, so replace the old instance
+        if *self == WallpaperSetter::Swaybg {
+            let _ = Command::new("pkill").args(["-x", "swaybg"]).status();
+        }
+
+        for command in self.commands(&image_path) {
+            let mut process = Command::new(&command[0]);
+            process.args(&command[1..]).stdout(Stdio::null());
+
+            if *self == WallpaperSetter::Swaybg {
+                process
+                    .stderr(Stdio::null())
+                    .spawn()
+                    .map_err(|e| command_error(&command, format!("failed to run: {}", e)))?;
+                continue;
+            }
+
+            let status = process
+                .status()
+                .map_err(|e| command_error(&command, format!("failed to run: {}", e)))?;
+
+            if !status.success() {
+                return Err(command_error(&command, format!("exited with {}", status)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn command_error(command: &[String], message: String) -> WalrusError {
+    WalrusError::Command {
+        command: command.join(" "),
+        message,
+    }
+}
+
+/// Replace {image} in a custom setter command with the single-quoted path
+pub fn expand_command_template(template: &str, image_path: &str) -> String {
+    template.replace("{image}", &shell_quote(image_path))
+}
+
+/// Single-quote a value for sh, closing and reopening the quotes around any '
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// file:// URI of an absolute path, percent-encoding everything but unreserved characters and /
+fn file_uri(path: &str) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+fn absolute_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setter_from_name() {
+        assert_eq!(
+            WallpaperSetter::from_name("SWWW"),
+            Some(WallpaperSetter::Swww)
+        );
+        assert_eq!(
+            WallpaperSetter::from_name("gnome"),
+            Some(WallpaperSetter::Gsettings)
+        );
+        assert_eq!(WallpaperSetter::from_name("nitrogen"), None);
+    }
+
+    #[test]
+    fn test_custom_command_template() {
+        let command = expand_command_template("swaybg -i {image}", "/tmp/it's.png");
+        assert_eq!(command, "swaybg -i '/tmp/it'\\''s.png'");
+    }
+
+    #[test]
+    fn test_gsettings_uri_is_percent_encoded() {
+        let commands = WallpaperSetter::Gsettings.commands("/home/me/My Walls/été #1.png");
+        assert_eq!(commands.len(), 2);
+        for command in commands {
+            assert_eq!(
+                command.last().unwrap(),
+                "file:///home/me/My%20Walls/%C3%A9t%C3%A9%20%231.png"
+            );
+        }
+    }
+}
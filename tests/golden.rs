@@ -0,0 +1,171 @@
+//! Golden tests for the whole pipeline: synthetic images are encoded as PNG,
+//! decoded, turned into a scheme and rendered, and every rendered file is
+//! compared with its snapshot in tests/snapshots. After an intended change to
+//! the color math, rerun with WALRUS_UPDATE_SNAPSHOTS=1 and review the diff.
+
+use std::env;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use image::{ImageFormat, Rgba, RgbaImage};
+use similar::TextDiff;
+use walrus::{Color, PywalGenerator, SequenceGenerator, TemplateGenerator, TemplateParser};
+
+// Terminal background alpha, the same as the walrus binary
+const ALPHA: u8 = 100;
+
+// Sky gradient over green hills with a sun
+fn landscape() -> RgbaImage {
+    RgbaImage::from_fn(320, 200, |x, y| {
+        let (dx, dy) = (x as f32 - 230.0, y as f32 - 60.0);
+        let hill = 130.0 + 20.0 * (x as f32 / 40.0).sin();
+        if dx * dx + dy * dy < 400.0 {
+            Rgba([250, 200, 60, 255])
+        } else if y as f32 > hill {
+            Rgba([40, 110 + (y % 20) as u8, 50, 255])
+        } else {
+            let t = y as f32 / 130.0;
+            Rgba([(70.0 + 120.0 * t) as u8, (120.0 + 80.0 * t) as u8, 220, 255])
+        }
+    })
+}
+
+// Shades of a single teal, so the accents are synthesized
+fn monochrome() -> RgbaImage {
+    RgbaImage::from_fn(256, 128, |x, _| {
+        let shade = x as f32 / 255.0;
+        Rgba([
+            (20.0 + 40.0 * shade) as u8,
+            (60.0 + 120.0 * shade) as u8,
+            (60.0 + 110.0 * shade) as u8,
+            255,
+        ])
+    })
+}
+
+// An orange disc with a purple ring on a transparent background
+fn sticker() -> RgbaImage {
+    RgbaImage::from_fn(200, 200, |x, y| {
+        let (dx, dy) = (x as f32 - 100.0, y as f32 - 100.0);
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance < 60.0 {
+            Rgba([240, 130, 40, 255])
+        } else if distance < 80.0 {
+            Rgba([120, 50, 160, 255])
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    })
+}
+
+fn png(image: &RgbaImage) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+        .unwrap();
+    bytes
+}
+
+// File name and contents of everything rendered from a scheme: the built-in
+// formats, the terminal sequences and the templates shipped in templates/
+fn render(colors: &[Color]) -> Vec<(String, String)> {
+    let template_gen = TemplateGenerator::new(colors.to_vec(), false);
+    let mut files = vec![
+        (
+            "colors.json".to_string(),
+            template_gen.generate_json_template(),
+        ),
+        (
+            "colors.sh".to_string(),
+            template_gen.generate_shell_template(),
+        ),
+        (
+            "sequences".to_string(),
+            SequenceGenerator::new(colors.to_vec(), ALPHA).generate_sequences(false),
+        ),
+    ];
+
+    let parser = TemplateParser::new(colors.to_vec());
+    let template_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates");
+    let mut templates: Vec<PathBuf> = fs::read_dir(template_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    templates.sort();
+    for path in templates {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let template = fs::read_to_string(&path).unwrap();
+        files.push((name, parser.parse_template(&template)));
+    }
+
+    files
+}
+
+fn assert_snapshots(case: &str, files: &[(String, String)]) {
+    let snapshot_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(case);
+
+    if env::var_os("WALRUS_UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(&snapshot_dir).unwrap();
+        for (name, contents) in files {
+            fs::write(snapshot_dir.join(name), contents).unwrap();
+        }
+        return;
+    }
+
+    let mut diffs = Vec::new();
+    for (name, contents) in files {
+        let path = snapshot_dir.join(name);
+        let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "Missing snapshot {}, run with WALRUS_UPDATE_SNAPSHOTS=1 to create it",
+                path.display()
+            )
+        });
+        if expected != *contents {
+            diffs.push(
+                TextDiff::from_lines(&expected, contents)
+                    .unified_diff()
+                    .header(&path.display().to_string(), "rendered")
+                    .to_string(),
+            );
+        }
+    }
+
+    assert!(
+        diffs.is_empty(),
+        "Output differs from the snapshots, rerun with WALRUS_UPDATE_SNAPSHOTS=1 if that is intended\n{}",
+        diffs.join("\n")
+    );
+}
+
+fn check(case: &str, image: &RgbaImage, light: bool) {
+    let colors = PywalGenerator::new()
+        .generate_from_memory(&png(image), 1.0, light)
+        .unwrap();
+    assert_eq!(colors.len(), 16);
+
+    assert_snapshots(case, &render(&colors));
+}
+
+#[test]
+fn test_landscape() {
+    check("landscape", &landscape(), false);
+}
+
+#[test]
+fn test_landscape_light() {
+    check("landscape-light", &landscape(), true);
+}
+
+#[test]
+fn test_monochrome() {
+    check("monochrome", &monochrome(), false);
+}
+
+#[test]
+fn test_sticker() {
+    check("sticker", &sticker(), false);
+}
@@ -0,0 +1,32 @@
+set -l foreground 394a6d
+set -l selection 394a6d
+set -l comment 394a6d
+set -l red 394a6d
+set -l orange b7b9b7
+set -l yellow 3d3d3d
+set -l green 3d3d3d
+set -l purple 3d3d3d
+set -l cyan 2a3f6a
+set -l pink 555f74
+
+set -g fish_color_normal $foreground
+set -g fish_color_command $red
+set -g fish_color_keyword $pink
+set -g fish_color_quote $yellow
+set -g fish_color_redirection $foreground
+set -g fish_color_end $orange
+set -g fish_color_error $red
+set -g fish_color_param $purple
+set -g fish_color_comment $comment
+set -g fish_color_selection --background=$selection
+set -g fish_color_search_match --background=$selection
+set -g fish_color_operator $green
+set -g fish_color_escape $pink
+set -g fish_color_autosuggestion $comment
+
+
+set -g fish_pager_color_progress $comment
+set -g fish_pager_color_prefix $cyan
+set -g fish_pager_color_completion $foreground
+set -g fish_pager_color_description $comment
+set -g fish_pager_color_selected_background --background=$selection
@@ -0,0 +1,37 @@
+local fg = "#394a6d"
+local bg = "#f4f7f4"
+local bright_fg = "#555f74"
+local bright_bg = "#2a3f6a"
+local white = "#b7b9b7"
+
+return {
+  background = bg,
+  foreground = fg,
+  cursor_bg = fg,
+  cursor_fg = black,
+  cursor_border = fg,
+  selection_fg = black,
+  selection_bg = fg,
+  scrollbar_thumb = fg,
+  split = white,
+  ansi = {
+    bright_bg,
+    "#2a3f6a",
+    "#1e673f",
+    "#394a6d",
+    "#4a5772",
+    "#6d6136",
+    "#555f74",
+    bright_fg,
+  },
+  brights = {
+    white,
+    "#3d3d3d",
+    "#b7b9b7",
+    "#2a3f6a",
+    "#1e673f",
+    "#394a6d",
+    "#4a5772",
+    fg,
+  },
+}
@@ -0,0 +1,22 @@
+#!/bin/bash
+# Pywal-style color variables generated with haishoku algorithm
+
+export background='#f4f7f4'
+export foreground='#3d3d3d'
+export cursor='#3d3d3d'
+export color0='#f4f7f4'
+export color1='#2a3f6a'
+export color2='#1e673f'
+export color3='#394a6d'
+export color4='#4a5772'
+export color5='#6d6136'
+export color6='#555f74'
+export color7='#3d3d3d'
+export color8='#b7b9b7'
+export color9='#2a3f6a'
+export color10='#1e673f'
+export color11='#394a6d'
+export color12='#4a5772'
+export color13='#6d6136'
+export color14='#555f74'
+export color15='#3d3d3d'
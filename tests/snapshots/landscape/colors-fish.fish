@@ -0,0 +1,32 @@
+set -l foreground 7396db
+set -l selection 7396db
+set -l comment 7396db
+set -l red 7396db
+set -l orange 525953
+set -l yellow c5c7c5
+set -l green c5c7c5
+set -l purple c5c7c5
+set -l cyan 4d7ddc
+set -l pink b9c4db
+
+set -g fish_color_normal $foreground
+set -g fish_color_command $red
+set -g fish_color_keyword $pink
+set -g fish_color_quote $yellow
+set -g fish_color_redirection $foreground
+set -g fish_color_end $orange
+set -g fish_color_error $red
+set -g fish_color_param $purple
+set -g fish_color_comment $comment
+set -g fish_color_selection --background=$selection
+set -g fish_color_search_match --background=$selection
+set -g fish_color_operator $green
+set -g fish_color_escape $pink
+set -g fish_color_autosuggestion $comment
+
+
+set -g fish_pager_color_progress $comment
+set -g fish_pager_color_prefix $cyan
+set -g fish_pager_color_completion $foreground
+set -g fish_pager_color_description $comment
+set -g fish_pager_color_selected_background --background=$selection
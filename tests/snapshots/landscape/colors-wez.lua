@@ -0,0 +1,37 @@
+local fg = "#7396db"
+local bg = "#19221a"
+local bright_fg = "#b9c4db"
+local bright_bg = "#4d7ddc"
+local white = "#525953"
+
+return {
+  background = bg,
+  foreground = fg,
+  cursor_bg = fg,
+  cursor_fg = black,
+  cursor_border = fg,
+  selection_fg = black,
+  selection_bg = fg,
+  scrollbar_thumb = fg,
+  split = white,
+  ansi = {
+    bright_bg,
+    "#4d7ddc",
+    "#6f9d84",
+    "#7396db",
+    "#9eb2db",
+    "#beb389",
+    "#b9c4db",
+    bright_fg,
+  },
+  brights = {
+    white,
+    "#c5c7c5",
+    "#525953",
+    "#4d7ddc",
+    "#6f9d84",
+    "#7396db",
+    "#9eb2db",
+    fg,
+  },
+}
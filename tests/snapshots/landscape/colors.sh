@@ -0,0 +1,22 @@
+#!/bin/bash
+# Pywal-style color variables generated with haishoku algorithm
+
+export background='#19221a'
+export foreground='#c5c7c5'
+export cursor='#c5c7c5'
+export color0='#19221a'
+export color1='#4d7ddc'
+export color2='#6f9d84'
+export color3='#7396db'
+export color4='#9eb2db'
+export color5='#beb389'
+export color6='#b9c4db'
+export color7='#c5c7c5'
+export color8='#525953'
+export color9='#4d7ddc'
+export color10='#6f9d84'
+export color11='#7396db'
+export color12='#9eb2db'
+export color13='#beb389'
+export color14='#b9c4db'
+export color15='#c5c7c5'
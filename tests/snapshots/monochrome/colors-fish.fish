@@ -0,0 +1,32 @@
+set -l foreground 6b38ad
+set -l selection 6b38ad
+set -l comment 6b38ad
+set -l red 6b38ad
+set -l orange 505454
+set -l yellow c4c6c6
+set -l green c4c6c6
+set -l purple c4c6c6
+set -l cyan 2a817b
+set -l pink ad38a2
+
+set -g fish_color_normal $foreground
+set -g fish_color_command $red
+set -g fish_color_keyword $pink
+set -g fish_color_quote $yellow
+set -g fish_color_redirection $foreground
+set -g fish_color_end $orange
+set -g fish_color_error $red
+set -g fish_color_param $purple
+set -g fish_color_comment $comment
+set -g fish_color_selection --background=$selection
+set -g fish_color_search_match --background=$selection
+set -g fish_color_operator $green
+set -g fish_color_escape $pink
+set -g fish_color_autosuggestion $comment
+
+
+set -g fish_pager_color_progress $comment
+set -g fish_pager_color_prefix $cyan
+set -g fish_pager_color_completion $foreground
+set -g fish_pager_color_description $comment
+set -g fish_pager_color_selected_background --background=$selection
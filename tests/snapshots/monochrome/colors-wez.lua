@@ -0,0 +1,37 @@
+local fg = "#6b38ad"
+local bg = "#161c1c"
+local bright_fg = "#ad38a2"
+local bright_bg = "#2a817b"
+local white = "#505454"
+
+return {
+  background = bg,
+  foreground = fg,
+  cursor_bg = fg,
+  cursor_fg = black,
+  cursor_border = fg,
+  selection_fg = black,
+  selection_bg = fg,
+  scrollbar_thumb = fg,
+  split = white,
+  ansi = {
+    bright_bg,
+    "#2a817b",
+    "#ad3840",
+    "#6b38ad",
+    "#7bad38",
+    "#3867ad",
+    "#ad38a2",
+    bright_fg,
+  },
+  brights = {
+    white,
+    "#c4c6c6",
+    "#505454",
+    "#2a817b",
+    "#ad3840",
+    "#6b38ad",
+    "#7bad38",
+    fg,
+  },
+}
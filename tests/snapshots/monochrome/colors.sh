@@ -0,0 +1,22 @@
+#!/bin/bash
+# Pywal-style color variables generated with haishoku algorithm
+
+export background='#161c1c'
+export foreground='#c4c6c6'
+export cursor='#c4c6c6'
+export color0='#161c1c'
+export color1='#2a817b'
+export color2='#ad3840'
+export color3='#6b38ad'
+export color4='#7bad38'
+export color5='#3867ad'
+export color6='#ad38a2'
+export color7='#c4c6c6'
+export color8='#505454'
+export color9='#2a817b'
+export color10='#ad3840'
+export color11='#6b38ad'
+export color12='#7bad38'
+export color13='#3867ad'
+export color14='#ad38a2'
+export color15='#c4c6c6'
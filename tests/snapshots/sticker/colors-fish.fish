@@ -0,0 +1,32 @@
+set -l foreground ef8128
+set -l selection ef8128
+set -l comment 78329f
+set -l red 78329f
+set -l orange 59535d
+set -l yellow c7c5c9
+set -l green c7c5c9
+set -l purple c7c5c9
+set -l cyan b65b60
+set -l pink 78329f
+
+set -g fish_color_normal $foreground
+set -g fish_color_command $red
+set -g fish_color_keyword $pink
+set -g fish_color_quote $yellow
+set -g fish_color_redirection $foreground
+set -g fish_color_end $orange
+set -g fish_color_error $red
+set -g fish_color_param $purple
+set -g fish_color_comment $comment
+set -g fish_color_selection --background=$selection
+set -g fish_color_search_match --background=$selection
+set -g fish_color_operator $green
+set -g fish_color_escape $pink
+set -g fish_color_autosuggestion $comment
+
+
+set -g fish_pager_color_progress $comment
+set -g fish_pager_color_prefix $cyan
+set -g fish_pager_color_completion $foreground
+set -g fish_pager_color_description $comment
+set -g fish_pager_color_selected_background --background=$selection
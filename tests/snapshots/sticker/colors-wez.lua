@@ -0,0 +1,37 @@
+local fg = "#ef8128"
+local bg = "#221a27"
+local bright_fg = "#78329f"
+local bright_bg = "#b65b60"
+local white = "#59535d"
+
+return {
+  background = bg,
+  foreground = fg,
+  cursor_bg = fg,
+  cursor_fg = black,
+  cursor_border = fg,
+  selection_fg = black,
+  selection_bg = fg,
+  scrollbar_thumb = fg,
+  split = white,
+  ansi = {
+    bright_bg,
+    "#b65b60",
+    "#ef8128",
+    "#78329f",
+    "#b65b60",
+    "#ef8128",
+    "#78329f",
+    bright_fg,
+  },
+  brights = {
+    white,
+    "#c7c5c9",
+    "#59535d",
+    "#78329f",
+    "#b65b60",
+    "#ef8128",
+    "#78329f",
+    fg,
+  },
+}
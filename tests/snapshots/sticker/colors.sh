@@ -0,0 +1,22 @@
+#!/bin/bash
+# Pywal-style color variables generated with haishoku algorithm
+
+export background='#221a27'
+export foreground='#c7c5c9'
+export cursor='#c7c5c9'
+export color0='#221a27'
+export color1='#b65b60'
+export color2='#ef8128'
+export color3='#78329f'
+export color4='#b65b60'
+export color5='#ef8128'
+export color6='#78329f'
+export color7='#c7c5c9'
+export color8='#59535d'
+export color9='#78329f'
+export color10='#b65b60'
+export color11='#ef8128'
+export color12='#78329f'
+export color13='#b65b60'
+export color14='#ef8128'
+export color15='#c7c5c9'